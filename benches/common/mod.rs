@@ -138,3 +138,57 @@ pub fn create_deep_path_codeowners(num_rules: usize) -> NamedTempFile {
     file.flush().unwrap();
     file
 }
+
+/// A monorepo of `num_services` services under `services/`, one of them
+/// `payments`, each a tree `depth` directories deep with three
+/// subdirectories and two files per directory.
+#[allow(dead_code)] // Only the speed benchmark uses it
+pub fn create_deep_repo(num_services: usize, depth: usize) -> tempfile::TempDir {
+    fn fill(dir: &std::path::Path, depth: usize) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("mod.rs"), "// content").unwrap();
+        fs::write(dir.join("README.md"), "# content").unwrap();
+        if depth > 0 {
+            for i in 0..3 {
+                fill(&dir.join(format!("level{}", i)), depth - 1);
+            }
+        }
+    }
+
+    let dir = tempdir().unwrap();
+    fill(&dir.path().join("services/payments"), depth);
+    for i in 1..num_services {
+        fill(&dir.path().join(format!("services/service{}", i)), depth);
+    }
+    dir
+}
+
+#[allow(dead_code)] // Only the speed benchmark uses it
+pub fn create_scoped_codeowners(extra: &str) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    let mut content = String::new();
+
+    writeln!(&mut content, "# Rules for one service").unwrap();
+    writeln!(&mut content, "/services/payments/**/*.rs @payments").unwrap();
+    writeln!(
+        &mut content,
+        "/services/payments/**/README.md @payments @docs"
+    )
+    .unwrap();
+    writeln!(
+        &mut content,
+        "/services/payments/level0/*.rs @payments-core"
+    )
+    .unwrap();
+    writeln!(
+        &mut content,
+        "/services/payments/level*/level2/ @payments-api"
+    )
+    .unwrap();
+    writeln!(&mut content, "{}", extra).unwrap();
+
+    use std::io::Write;
+    file.write_all(content.as_bytes()).unwrap();
+    file.flush().unwrap();
+    file
+}
@@ -182,13 +182,15 @@ fn benchmark_optimization_modes(c: &mut Criterion) {
     group.finish();
 }
 
+type CodeownersGenerator = Box<dyn Fn(usize) -> tempfile::NamedTempFile>;
+
 // Peak memory usage for different patterns
 fn benchmark_pattern_memory_impact(c: &mut Criterion) {
     let mut group = c.benchmark_group("pattern_memory_impact");
     group.sample_size(10);
 
     // Compare memory usage of different pattern types
-    let patterns: Vec<(&str, Box<dyn Fn(usize) -> tempfile::NamedTempFile>)> = vec![
+    let patterns: Vec<(&str, CodeownersGenerator)> = vec![
         (
             "direct_paths",
             Box::new(|n| {
@@ -1,8 +1,12 @@
 mod common;
 
+use codeowners_validation::files::FileSource;
 use codeowners_validation::parser::parse_codeowners_file;
+use codeowners_validation::validators::context::ValidationContext;
 use codeowners_validation::validators::duplicate_patterns::validate_duplicates;
 use codeowners_validation::validators::exists::validate_directory;
+use codeowners_validation::validators::validator::{run_validator, ValidatorArgs};
+use codeowners_validation::NoopReporter;
 use common::*;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 
@@ -124,6 +128,59 @@ fn benchmark_pathological_cases(c: &mut Criterion) {
     group.finish();
 }
 
+// Benchmark subtree pruning on a deep tree. With rules scoped to one
+// service the other services are skipped; a root-level rule that never
+// matches forces the full walk for comparison.
+fn benchmark_deep_tree_pruning(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deep_tree_pruning");
+    group.sample_size(10);
+
+    let repo = create_deep_repo(20, 6);
+    for (name, extra) in [
+        ("scoped_rules", ""),
+        ("full_walk", "*.never-matches @nobody"),
+    ] {
+        let file = create_scoped_codeowners(extra);
+        let (rules, _) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let result = validate_directory(repo.path(), &rules).unwrap();
+                std::hint::black_box(result);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+// The whole run, as the CLI does it: `exists` on its own walks only the
+// directories its rules reach, while checks that need every file share one
+// listing of the tree
+fn benchmark_run_validator_deep_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run_validator_deep_tree");
+    group.sample_size(10);
+
+    let repo = create_deep_repo(20, 6);
+    let file = create_scoped_codeowners("");
+    let (rules, _) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
+    for (name, checks) in [("exists_only", "exists"), ("all_checks", "all")] {
+        let args = ValidatorArgs::from_env(checks).unwrap();
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let ctx = ValidationContext::new(&rules)
+                    .with_repo_root(repo.path())
+                    .with_file_source(FileSource::Worktree);
+                let result = run_validator(&args, &ctx, &NoopReporter).unwrap();
+                std::hint::black_box(result);
+            });
+        });
+    }
+
+    group.finish();
+}
+
 // Quick smoke test for development
 fn benchmark_quick_check(c: &mut Criterion) {
     let mut group = c.benchmark_group("quick_check");
@@ -151,6 +208,8 @@ criterion_group!(
     benchmark_duplicate_detection_speed,
     benchmark_end_to_end_speed,
     benchmark_pathological_cases,
+    benchmark_deep_tree_pruning,
+    benchmark_run_validator_deep_tree,
     benchmark_quick_check
 );
 criterion_main!(benches);
@@ -16,11 +16,8 @@ else
     CHECKS="all"
 fi
 
-if [ -n "${INPUT_PATH:-}" ]; then
-    PATH_ARG="$INPUT_PATH"
-else
-    PATH_ARG=".github/CODEOWNERS"
-fi
+# An empty path lets the validator find the file in the forge's lookup order
+PATH_ARG="${INPUT_PATH:-}"
 
 # Parse command line arguments if provided
 while [[ $# -gt 0 ]]; do
@@ -43,32 +40,48 @@ while [[ $# -gt 0 ]]; do
     esac
 done
 
+# Inputs left empty in the workflow mean "use the default", which for
+# numeric options is only true when the variable is not set at all
+for var in $(compgen -e); do
+    if [[ "$var" == INPUT_* && -z "${!var}" ]]; then
+        unset "$var"
+    fi
+done
+
 # Set thread limit for CI
 export CODEOWNERS_THREADS="${CODEOWNERS_THREADS:-2}"
 
 echo "::group::CODEOWNERS Validation"
 echo "🔍 Running CODEOWNERS validation"
 echo "📋 Checks: $CHECKS"
-echo "📄 Path: $PATH_ARG"
+echo "📄 Path: ${PATH_ARG:-auto-detect}"
 echo ""
 
-# Check if file exists
-if [ ! -f "$PATH_ARG" ]; then
-    echo "::error::CODEOWNERS file not found at $PATH_ARG"
-    echo "❌ Error: CODEOWNERS file not found at $PATH_ARG"
-    echo "::endgroup::"
-    exit 1
+ARGS=(--checks "$CHECKS")
+if [ -n "$PATH_ARG" ]; then
+    ARGS+=(--path "$PATH_ARG")
 fi
 
 # Run validation
 set +e
-codeowners-validation --checks "$CHECKS" --path "$PATH_ARG"
+codeowners-validation "${ARGS[@]}"
 EXIT_CODE=$?
 set -e
 
-# Set output
+# Exit codes are documented in the README:
+# 0 clean, 1 findings, 2 usage/config error, 3 file not found, 4 internal error
+case $EXIT_CODE in
+    0) PASSED="true" ;;
+    1) PASSED="false" ;;
+    2) PASSED="false"; echo "::error::Invalid CODEOWNERS validation configuration" ;;
+    3) PASSED="false"; echo "::error::CODEOWNERS file not found at ${PATH_ARG:-any of the default locations}" ;;
+    *) PASSED="false"; echo "::error::CODEOWNERS validation could not complete (exit code $EXIT_CODE)" ;;
+esac
+
+# Set outputs
 if [ -n "${GITHUB_OUTPUT:-}" ]; then
-    echo "validation-passed=$([[ $EXIT_CODE -eq 0 ]] && echo "true" || echo "false")" >> $GITHUB_OUTPUT
+    echo "validation-passed=$PASSED" >> $GITHUB_OUTPUT
+    echo "exit-code=$EXIT_CODE" >> $GITHUB_OUTPUT
 fi
 
 echo "::endgroup::"
@@ -0,0 +1,502 @@
+//! Lossless concrete syntax tree for CODEOWNERS files.
+//!
+//! Unlike [`crate::parser`], which only keeps what validation needs, the
+//! tree keeps every byte of the input: comments, blank lines, indentation,
+//! alignment whitespace, trailing comments and line endings. Writing an
+//! unmodified tree back out reproduces the input exactly, and edits only
+//! touch the lines they are applied to.
+
+use crate::parser::tokenize;
+use std::fmt;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxTree {
+    bom: bool,
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    /// Only possible on the last line
+    None,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::None => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    kind: LineKind,
+    ending: LineEnding,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineKind {
+    /// Empty or whitespace-only line, whitespace preserved
+    Blank(String),
+    Comment(Comment),
+    Rule(RuleLine),
+    /// A line that is not valid UTF-8, kept verbatim
+    Raw(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    indent: String,
+    text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleLine {
+    indent: String,
+    pattern: String,
+    owners: Vec<Owner>,
+    trailing: String,
+    comment: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Owner {
+    separator: String,
+    name: String,
+}
+
+impl SyntaxTree {
+    pub fn parse(content: &[u8]) -> Self {
+        let (bom, mut rest) = match content.strip_prefix(UTF8_BOM) {
+            Some(stripped) => (true, stripped),
+            None => (false, content),
+        };
+
+        let mut lines = Vec::new();
+        while !rest.is_empty() {
+            let (raw, ending, next) = match rest.iter().position(|&b| b == b'\n') {
+                Some(idx) if idx > 0 && rest[idx - 1] == b'\r' => {
+                    (&rest[..idx - 1], LineEnding::CrLf, &rest[idx + 1..])
+                }
+                Some(idx) => (&rest[..idx], LineEnding::Lf, &rest[idx + 1..]),
+                None => (rest, LineEnding::None, &rest[rest.len()..]),
+            };
+            lines.push(Line {
+                kind: LineKind::parse(raw),
+                ending,
+            });
+            rest = next;
+        }
+
+        SyntaxTree { bom, lines }
+    }
+
+    pub fn parse_str(content: &str) -> Self {
+        Self::parse(content.as_bytes())
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// The line with the given 1-based number.
+    pub fn line(&self, line_number: usize) -> Option<&Line> {
+        self.lines.get(line_number.checked_sub(1)?)
+    }
+
+    /// Rule lines with their 1-based line numbers.
+    pub fn rules(&self) -> impl Iterator<Item = (usize, &RuleLine)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, line)| match &line.kind {
+                LineKind::Rule(rule) => Some((idx + 1, rule)),
+                _ => None,
+            })
+    }
+
+    /// Full-line comments with their 1-based line numbers.
+    pub fn comments(&self) -> impl Iterator<Item = (usize, &Comment)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, line)| match &line.kind {
+                LineKind::Comment(comment) => Some((idx + 1, comment)),
+                _ => None,
+            })
+    }
+
+    pub fn rule_mut(&mut self, line_number: usize) -> Option<&mut RuleLine> {
+        let line = self.lines.get_mut(line_number.checked_sub(1)?)?;
+        match &mut line.kind {
+            LineKind::Rule(rule) => Some(rule),
+            _ => None,
+        }
+    }
+
+    /// Removes a line together with its line ending.
+    pub fn remove_line(&mut self, line_number: usize) -> Option<Line> {
+        let idx = line_number.checked_sub(1)?;
+        if idx >= self.lines.len() {
+            return None;
+        }
+        Some(self.lines.remove(idx))
+    }
+
+    /// Inserts `text` as a new line before `line_number`, or at the end if
+    /// `line_number` is past the last line. The new line uses the line
+    /// ending style of the file.
+    pub fn insert_line(&mut self, line_number: usize, text: &str) {
+        let ending = self.dominant_ending();
+        let idx = line_number.saturating_sub(1).min(self.lines.len());
+
+        // Appending after a last line without a terminator needs one first
+        if idx == self.lines.len() {
+            if let Some(last) = self.lines.last_mut() {
+                if last.ending == LineEnding::None {
+                    last.ending = ending;
+                    self.lines.push(Line {
+                        kind: LineKind::parse(text.as_bytes()),
+                        ending: LineEnding::None,
+                    });
+                    return;
+                }
+            }
+        }
+
+        self.lines.insert(
+            idx,
+            Line {
+                kind: LineKind::parse(text.as_bytes()),
+                ending,
+            },
+        );
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if self.bom {
+            out.extend_from_slice(UTF8_BOM);
+        }
+        for line in &self.lines {
+            line.write_to(&mut out);
+        }
+        out
+    }
+
+    fn dominant_ending(&self) -> LineEnding {
+        let crlf = self
+            .lines
+            .iter()
+            .filter(|line| line.ending == LineEnding::CrLf)
+            .count();
+        let lf = self
+            .lines
+            .iter()
+            .filter(|line| line.ending == LineEnding::Lf)
+            .count();
+        if crlf > lf {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        }
+    }
+}
+
+/// Lossy for lines that are not valid UTF-8; use [`SyntaxTree::to_bytes`]
+/// for an exact copy.
+impl fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.to_bytes()))
+    }
+}
+
+impl Line {
+    pub fn kind(&self) -> &LineKind {
+        &self.kind
+    }
+
+    pub fn ending(&self) -> LineEnding {
+        self.ending
+    }
+
+    fn write_to(&self, out: &mut Vec<u8>) {
+        match &self.kind {
+            LineKind::Blank(whitespace) => out.extend_from_slice(whitespace.as_bytes()),
+            LineKind::Comment(comment) => {
+                out.extend_from_slice(comment.indent.as_bytes());
+                out.extend_from_slice(comment.text.as_bytes());
+            }
+            LineKind::Rule(rule) => out.extend_from_slice(rule.to_string().as_bytes()),
+            LineKind::Raw(bytes) => out.extend_from_slice(bytes),
+        }
+        out.extend_from_slice(self.ending.as_str().as_bytes());
+    }
+}
+
+impl LineKind {
+    fn parse(raw: &[u8]) -> Self {
+        let Ok(text) = std::str::from_utf8(raw) else {
+            return LineKind::Raw(raw.to_vec());
+        };
+
+        let body = text.trim_start();
+        let indent = &text[..text.len() - body.len()];
+        if body.is_empty() {
+            return LineKind::Blank(text.to_string());
+        }
+        if body.starts_with('#') {
+            return LineKind::Comment(Comment {
+                indent: indent.to_string(),
+                text: body.to_string(),
+            });
+        }
+
+        LineKind::Rule(RuleLine::parse(indent, body))
+    }
+}
+
+impl Comment {
+    /// The comment including its leading `#`.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl RuleLine {
+    fn parse(indent: &str, body: &str) -> Self {
+        let tokens = tokenize(body);
+        let end = tokens.comment.unwrap_or(body.len());
+
+        let mut pattern = "";
+        let mut owners = Vec::with_capacity(tokens.spans.len().saturating_sub(1));
+        let mut previous_end = 0;
+        for (idx, span) in tokens.spans.iter().enumerate() {
+            if idx == 0 {
+                pattern = &body[span.clone()];
+            } else {
+                owners.push(Owner {
+                    separator: body[previous_end..span.start].to_string(),
+                    name: body[span.clone()].to_string(),
+                });
+            }
+            previous_end = span.end;
+        }
+
+        RuleLine {
+            indent: indent.to_string(),
+            pattern: pattern.to_string(),
+            owners,
+            trailing: body[previous_end..end].to_string(),
+            comment: tokens.comment.map(|start| body[start..].to_string()),
+        }
+    }
+
+    /// The pattern exactly as written, including any escapes.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn owners(&self) -> impl Iterator<Item = &str> {
+        self.owners.iter().map(|owner| owner.name.as_str())
+    }
+
+    /// The trailing comment including its leading `#`.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Replaces the pattern, shifting the gap before the first owner so
+    /// aligned owner columns stay aligned where possible.
+    pub fn set_pattern(&mut self, pattern: &str) {
+        if let Some(first) = self.owners.first_mut() {
+            if first.separator.bytes().all(|b| b == b' ') {
+                let column = self.pattern.len() + first.separator.len();
+                let gap = column.saturating_sub(pattern.len()).max(1);
+                first.separator = " ".repeat(gap);
+            }
+        }
+        self.pattern = pattern.to_string();
+    }
+
+    /// Replaces the owners. The gap after the pattern is kept; new owners
+    /// are separated by single spaces.
+    pub fn set_owners<I, S>(&mut self, owners: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let first_separator = self
+            .owners
+            .first()
+            .map_or_else(|| " ".to_string(), |owner| owner.separator.clone());
+        self.owners = owners
+            .into_iter()
+            .enumerate()
+            .map(|(idx, name)| Owner {
+                separator: if idx == 0 {
+                    first_separator.clone()
+                } else {
+                    " ".to_string()
+                },
+                name: name.into(),
+            })
+            .collect();
+    }
+
+    pub fn add_owner(&mut self, name: &str) {
+        self.owners.push(Owner {
+            separator: " ".to_string(),
+            name: name.to_string(),
+        });
+    }
+
+    /// Removes every occurrence of `name`. Returns whether anything changed.
+    pub fn remove_owner(&mut self, name: &str) -> bool {
+        let Some(idx) = self.owners.iter().position(|owner| owner.name == name) else {
+            return false;
+        };
+        // Keep the alignment gap of the first owner for whoever moves up
+        let separator = self.owners[idx].separator.clone();
+        self.owners.retain(|owner| owner.name != name);
+        if idx == 0 {
+            if let Some(first) = self.owners.first_mut() {
+                first.separator = separator;
+            }
+        }
+        true
+    }
+}
+
+impl fmt::Display for RuleLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.indent)?;
+        f.write_str(&self.pattern)?;
+        for owner in &self.owners {
+            f.write_str(&owner.separator)?;
+            f.write_str(&owner.name)?;
+        }
+        f.write_str(&self.trailing)?;
+        if let Some(comment) = &self.comment {
+            f.write_str(comment)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(input: &[u8]) {
+        assert_eq!(SyntaxTree::parse(input).to_bytes(), input);
+    }
+
+    #[test]
+    fn roundtrips_exactly() {
+        roundtrip(b"");
+        roundtrip(b"\n\n");
+        roundtrip(b"# header\n\n/src/   @a  @b\n");
+        roundtrip(b"  indented/ \t@a\t# trailing comment  \n");
+        roundtrip(b"\xEF\xBB\xBF*.md @docs\r\n/src/ @a\nno-newline @b");
+        roundtrip(b"/caf\xE9/ @b\n   \n\t# comment\n");
+        roundtrip(b"/dangling-cr @a\r");
+        roundtrip(b"/no-owners   \n");
+        roundtrip(b"docs/My\\ Folder/  @a \\#not-a-comment # comment\n");
+    }
+
+    #[test]
+    fn navigates_rules_and_comments() {
+        let tree = SyntaxTree::parse_str("# Docs\n*.md  @docs @b # shared\n\n/src/ @a\n");
+        let rules: Vec<_> = tree.rules().collect();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].0, 2);
+        assert_eq!(rules[0].1.pattern(), "*.md");
+        assert_eq!(rules[0].1.owners().collect::<Vec<_>>(), vec!["@docs", "@b"]);
+        assert_eq!(rules[0].1.comment(), Some("# shared"));
+        assert_eq!(rules[1].0, 4);
+
+        let comments: Vec<_> = tree.comments().collect();
+        assert_eq!(comments, vec![(1, comments[0].1)]);
+        assert_eq!(comments[0].1.text(), "# Docs");
+        assert!(matches!(tree.line(3).unwrap().kind(), LineKind::Blank(_)));
+    }
+
+    #[test]
+    fn escaped_spaces_stay_in_the_pattern() {
+        let tree = SyntaxTree::parse_str("docs/My\\ Folder/ @a # team\n");
+        let (_, rule) = tree.rules().next().unwrap();
+        assert_eq!(rule.pattern(), "docs/My\\ Folder/");
+        assert_eq!(rule.owners().collect::<Vec<_>>(), vec!["@a"]);
+        assert_eq!(rule.comment(), Some("# team"));
+    }
+
+    #[test]
+    fn edits_keep_other_lines_identical() {
+        let input = "# keep\r\n/src/     @a # note\r\n  /docs/ @b\r\n";
+        let mut tree = SyntaxTree::parse_str(input);
+        tree.rule_mut(2).unwrap().set_pattern("/lib/");
+        tree.rule_mut(3).unwrap().add_owner("@c");
+        assert_eq!(
+            tree.to_string(),
+            "# keep\r\n/lib/     @a # note\r\n  /docs/ @b @c\r\n"
+        );
+    }
+
+    #[test]
+    fn set_pattern_keeps_owner_column() {
+        let mut tree = SyntaxTree::parse_str("/a/        @x\n");
+        tree.rule_mut(1).unwrap().set_pattern("/longer/path/");
+        assert_eq!(tree.to_string(), "/longer/path/ @x\n");
+
+        let mut tree = SyntaxTree::parse_str("/a/        @x\n");
+        tree.rule_mut(1).unwrap().set_pattern("/bcd/");
+        assert_eq!(tree.to_string(), "/bcd/      @x\n");
+    }
+
+    #[test]
+    fn owner_edits() {
+        let mut tree = SyntaxTree::parse_str("*.rs    @a @b @a\n");
+        let rule = tree.rule_mut(1).unwrap();
+        assert!(rule.remove_owner("@a"));
+        assert!(!rule.remove_owner("@missing"));
+        assert_eq!(tree.to_string(), "*.rs    @b\n");
+
+        tree.rule_mut(1).unwrap().set_owners(["@x", "@y"]);
+        assert_eq!(tree.to_string(), "*.rs    @x @y\n");
+    }
+
+    #[test]
+    fn insert_and_remove_lines() {
+        let mut tree = SyntaxTree::parse_str("/a/ @a\r\n/b/ @b");
+        tree.insert_line(10, "/c/ @c");
+        assert_eq!(tree.to_string(), "/a/ @a\r\n/b/ @b\r\n/c/ @c");
+
+        tree.insert_line(1, "# first");
+        assert_eq!(tree.to_string(), "# first\r\n/a/ @a\r\n/b/ @b\r\n/c/ @c");
+
+        tree.remove_line(4);
+        assert_eq!(tree.to_string(), "# first\r\n/a/ @a\r\n/b/ @b\r\n");
+        assert!(tree.remove_line(9).is_none());
+    }
+
+    #[test]
+    fn edits_leave_raw_lines_untouched() {
+        let input = b"/caf\xE9/ @b\n/src/ @a\n";
+        let mut tree = SyntaxTree::parse(input);
+        assert!(matches!(tree.line(1).unwrap().kind(), LineKind::Raw(_)));
+        tree.rule_mut(2).unwrap().add_owner("@c");
+        assert_eq!(tree.to_bytes(), b"/caf\xE9/ @b\n/src/ @a @c\n");
+    }
+}
@@ -0,0 +1,738 @@
+use crate::discovery::Forge;
+use crate::parser::{CodeOwnerRule, InvalidLine, InvalidReason};
+use crate::validators::coverage::Coverage;
+use crate::validators::gitignored::IgnoredFinding;
+use crate::validators::limits::LimitFinding;
+use crate::validators::overbroad::{Exceeded, OverbroadFinding};
+use crate::validators::path_normalization::{PathFinding, PathIssue};
+use crate::validators::pattern_style::{StyleFinding, StyleLint};
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// The part of a CODEOWNERS line a label points at.
+///
+/// Positions are resolved against the source text when rendering, so
+/// validators only need the rule's line number to produce a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Highlight {
+    /// The whole line, excluding leading and trailing whitespace
+    Line,
+    /// The pattern token
+    Pattern,
+    /// The owner token at the given index
+    Owner(usize),
+    /// An explicit byte range within the line
+    Columns(Range<usize>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub line_number: usize,
+    pub highlight: Highlight,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(line_number: usize, highlight: Highlight, message: impl Into<String>) -> Self {
+        Label {
+            line_number,
+            highlight,
+            message: message.into(),
+        }
+    }
+}
+
+/// How confident we are that a suggestion can be applied without review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applicability {
+    MachineApplicable,
+    MaybeIncorrect,
+}
+
+/// A replacement for the primary label's highlighted text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub message: String,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub primary: Option<Label>,
+    pub notes: Vec<Label>,
+    pub help: Vec<String>,
+    pub suggestion: Option<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            message: message.into(),
+            primary: None,
+            notes: Vec::new(),
+            help: Vec::new(),
+            suggestion: None,
+        }
+    }
+
+    pub fn error(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, code, message)
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, code, message)
+    }
+
+    pub fn with_primary(
+        mut self,
+        line_number: usize,
+        highlight: Highlight,
+        message: impl Into<String>,
+    ) -> Self {
+        self.primary = Some(Label::new(line_number, highlight, message));
+        self
+    }
+
+    pub fn with_note(
+        mut self,
+        line_number: usize,
+        highlight: Highlight,
+        message: impl Into<String>,
+    ) -> Self {
+        self.notes.push(Label::new(line_number, highlight, message));
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    pub fn with_suggestion(
+        mut self,
+        message: impl Into<String>,
+        replacement: impl Into<String>,
+        applicability: Applicability,
+    ) -> Self {
+        self.suggestion = Some(Suggestion {
+            message: message.into(),
+            replacement: replacement.into(),
+            applicability,
+        });
+        self
+    }
+
+    /// Line the diagnostic is reported on, if it points into the file.
+    pub fn line_number(&self) -> Option<usize> {
+        self.primary.as_ref().map(|label| label.line_number)
+    }
+
+    pub fn exists(rule: &CodeOwnerRule) -> Self {
+        Diagnostic::error(
+            "exists",
+            format!(
+                "pattern `{}` does not match any file in the repository",
+                rule.original_path
+            ),
+        )
+        .with_primary(
+            rule.line_number,
+            Highlight::Pattern,
+            "no file or directory matches this pattern",
+        )
+        .with_help("remove the rule or update the pattern to point at an existing path")
+    }
+
+    /// A directory pattern that only matches files.
+    pub fn not_a_directory(rule: &CodeOwnerRule) -> Self {
+        let replacement = rule.original_path.trim_end_matches('/');
+        Diagnostic::error(
+            "exists",
+            format!(
+                "directory pattern `{}` only matches files",
+                rule.original_path
+            ),
+        )
+        .with_primary(
+            rule.line_number,
+            Highlight::Pattern,
+            "a trailing `/` only matches directories",
+        )
+        .with_suggestion(
+            format!("remove the trailing slash: `{}`", replacement),
+            replacement,
+            Applicability::MachineApplicable,
+        )
+    }
+
+    /// An anchored pattern without a trailing slash that names a directory.
+    pub fn implicit_directory(rule: &CodeOwnerRule) -> Self {
+        let replacement = format!("{}/", rule.original_path);
+        Diagnostic::warning(
+            "exists",
+            format!(
+                "`{}` is a directory, but the pattern does not end with `/`",
+                rule.original_path
+            ),
+        )
+        .with_primary(rule.line_number, Highlight::Pattern, "names a directory")
+        .with_help(
+            "without a trailing `/` the pattern matches a file or a directory of that name; \
+             ownership of the directory's contents relies on that implicit rule",
+        )
+        .with_suggestion(
+            format!("make the directory explicit: `{}`", replacement),
+            replacement,
+            Applicability::MachineApplicable,
+        )
+    }
+
+    /// A pattern that reaches inside a submodule.
+    pub fn inside_submodule(rule: &CodeOwnerRule, submodule: &Path) -> Self {
+        Diagnostic::warning(
+            "exists",
+            format!(
+                "pattern `{}` reaches inside submodule `{}`",
+                rule.original_path,
+                submodule.display()
+            ),
+        )
+        .with_primary(rule.line_number, Highlight::Pattern, "inside a submodule")
+        .with_help("the forge applies the submodule's own CODEOWNERS to its files, not this one")
+        .with_help(format!(
+            "own the submodule as a whole with `/{}`, or move the rule into the submodule",
+            submodule.display()
+        ))
+    }
+
+    /// `first` is the earlier rule the duplicate collides with, if known.
+    pub fn duplicate_pattern(rule: &CodeOwnerRule, first: Option<&CodeOwnerRule>) -> Self {
+        let mut diagnostic = Diagnostic::error(
+            "duplicate_patterns",
+            format!("pattern `{}` is defined more than once", rule.original_path),
+        )
+        .with_primary(rule.line_number, Highlight::Pattern, "duplicate pattern");
+
+        if let Some(first) = first {
+            diagnostic =
+                diagnostic.with_note(first.line_number, Highlight::Pattern, "first defined here");
+            if first.original_path != rule.original_path {
+                diagnostic = diagnostic.with_help(format!(
+                    "`{}` and `{}` normalize to the same pattern `{}`",
+                    first.original_path, rule.original_path, rule.pattern
+                ));
+            }
+        }
+
+        diagnostic.with_help(
+            "only the last matching rule takes effect; merge the owners into a single line",
+        )
+    }
+
+    pub fn no_owners(rule: &CodeOwnerRule) -> Self {
+        Diagnostic::warning(
+            "no_owners",
+            format!("pattern `{}` has no owners", rule.original_path),
+        )
+        .with_primary(
+            rule.line_number,
+            Highlight::Pattern,
+            "matching files are left without code owners",
+        )
+        .with_help("a rule without owners removes ownership set by earlier rules")
+        .with_help(
+            "add owners, or allow-list the pattern if leaving these files unowned is intentional",
+        )
+    }
+
+    /// A rule that only matches files git ignores.
+    pub fn gitignored(finding: &IgnoredFinding) -> Self {
+        let rule = &finding.rule;
+        Diagnostic::warning(
+            "gitignored",
+            format!(
+                "pattern `{}` only matches files ignored by git",
+                rule.original_path
+            ),
+        )
+        .with_primary(
+            rule.line_number,
+            Highlight::Pattern,
+            match finding.ignored {
+                1 => "matches 1 ignored file".to_string(),
+                count => format!("matches {} ignored files", count),
+            },
+        )
+        .with_help(format!(
+            "`{}` is ignored, and ignored files are never part of a change, so this rule has no effect on reviews",
+            finding.example.display()
+        ))
+        .with_help("remove the rule, or stop ignoring the files it is meant to own")
+    }
+
+    /// A rule that owns more of the repository than configured. The rules it
+    /// takes files from are pointed at, up to a few.
+    pub fn overbroad(finding: &OverbroadFinding) -> Self {
+        const EXAMPLES: usize = 3;
+
+        let rule = &finding.rule;
+        let reasons: Vec<String> = finding
+            .exceeded
+            .iter()
+            .map(|exceeded| match exceeded {
+                Exceeded::Share { limit } => format!("more than {}% of the repository", limit),
+                Exceeded::Files { limit } => format!("more than {} files", limit),
+                Exceeded::OverriddenRules { limit } => format!(
+                    "takes files from {} earlier rules, more than {}",
+                    finding.overridden.len(),
+                    limit
+                ),
+            })
+            .collect();
+        let mut diagnostic = Diagnostic::warning(
+            "overbroad",
+            format!(
+                "pattern `{}` owns {} of {} files ({:.1}%)",
+                rule.original_path,
+                finding.effective,
+                finding.files,
+                finding.share()
+            ),
+        )
+        .with_primary(rule.line_number, Highlight::Pattern, reasons.join("; "));
+
+        for (overridden, count) in finding.overridden.iter().take(EXAMPLES) {
+            diagnostic = diagnostic.with_note(
+                overridden.line_number,
+                Highlight::Pattern,
+                match count {
+                    1 => "loses 1 file to the later rule".to_string(),
+                    count => format!("loses {} files to the later rule", count),
+                },
+            );
+        }
+
+        if !finding.losers.is_empty() {
+            let mut losers: Vec<String> = finding
+                .losers
+                .iter()
+                .take(EXAMPLES)
+                .map(|(owner, count)| format!("{} ({})", owner, count))
+                .collect();
+            if finding.losers.len() > EXAMPLES {
+                losers.push(format!("and {} more", finding.losers.len() - EXAMPLES));
+            }
+            diagnostic =
+                diagnostic.with_help(format!("owners losing files: {}", losers.join(", ")));
+        }
+
+        diagnostic.with_help(
+            "later rules win, so a broad pattern near the end overrides specific ones; \
+             move it above them or narrow the pattern",
+        )
+    }
+
+    /// `shadowed` exists but the forge only reads `effective`.
+    pub fn shadowed_codeowners(shadowed: &Path, effective: &Path, forge: Forge) -> Self {
+        Diagnostic::warning(
+            "multiple_codeowners",
+            format!(
+                "`{}` is ignored because `{}` takes precedence",
+                shadowed.display(),
+                effective.display()
+            ),
+        )
+        .with_help(format!(
+            "{} only reads the first CODEOWNERS file it finds, so edits to `{}` have no effect",
+            forge,
+            shadowed.display()
+        ))
+        .with_help("merge its rules into the effective file and delete it")
+    }
+
+    /// Errors for crossed limits and warnings for nearly reached ones, with
+    /// what the forge will do once the limit is crossed.
+    pub fn limit(finding: &LimitFinding, forge: Forge) -> Self {
+        let severity = if finding.is_exceeded() {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+
+        match *finding {
+            LimitFinding::FileSize { size, limit } => {
+                let message = if finding.is_exceeded() {
+                    format!(
+                        "CODEOWNERS file is {}, over the {} limit",
+                        megabytes(size),
+                        megabytes(limit)
+                    )
+                } else {
+                    format!(
+                        "CODEOWNERS file is {}, {:.0}% of the {} limit",
+                        megabytes(size),
+                        size as f64 * 100.0 / limit as f64,
+                        megabytes(limit)
+                    )
+                };
+                Diagnostic::new(severity, "limits", message).with_help(format!(
+                    "{} ignores the entire file above the limit, so no code owners are requested for review",
+                    forge
+                ))
+            }
+            LimitFinding::RuleCount { rules, limit } => Diagnostic::new(
+                severity,
+                "limits",
+                format!(
+                    "CODEOWNERS file has {} rules, the limit is {}",
+                    rules, limit
+                ),
+            )
+            .with_help(format!(
+                "{} may ignore every rule after the first {}",
+                forge, limit
+            )),
+            LimitFinding::LineLength {
+                line_number,
+                length,
+                limit,
+            } => Diagnostic::new(
+                severity,
+                "limits",
+                format!("line is {} bytes long, the limit is {}", length, limit),
+            )
+            .with_primary(line_number, Highlight::Line, "line too long")
+            .with_help(format!(
+                "{} will skip this line, so matching files fall back to earlier rules",
+                forge
+            )),
+            LimitFinding::OwnersPerRule {
+                line_number,
+                owners,
+                limit,
+            } => Diagnostic::new(
+                severity,
+                "limits",
+                format!("rule has {} owners, the limit is {}", owners, limit),
+            )
+            .with_primary(
+                line_number,
+                Highlight::Owner(limit),
+                "owners from here on are over the limit",
+            )
+            .with_help("assign a team instead of listing individual owners"),
+        }
+    }
+
+    pub fn path_normalization(finding: &PathFinding) -> Self {
+        let rule = &finding.rule;
+        let found: Vec<String> = finding
+            .issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+
+        let message = format!(
+            "pattern `{}` is not a normalized repository path",
+            rule.original_path
+        );
+        // An absolute path that cannot be mapped into the repository is
+        // only a guess from how the pattern starts
+        let guessed = finding.replacement.is_none()
+            && finding
+                .issues
+                .iter()
+                .all(|issue| *issue == PathIssue::AbsolutePath);
+        let mut diagnostic = match guessed {
+            true => Diagnostic::warning("path_normalization", message),
+            false => Diagnostic::error("path_normalization", message),
+        }
+        .with_primary(rule.line_number, Highlight::Pattern, found.join(", "));
+        for issue in &finding.issues {
+            diagnostic = diagnostic.with_help(issue.explanation());
+        }
+
+        match &finding.replacement {
+            Some(replacement) => diagnostic.with_suggestion(
+                format!("write the pattern as `{}`", replacement),
+                replacement.clone(),
+                Applicability::MachineApplicable,
+            ),
+            None => diagnostic.with_help("write the pattern relative to the repository root"),
+        }
+    }
+
+    pub fn pattern_style(finding: &StyleFinding) -> Self {
+        let rule = &finding.rule;
+        let original = &rule.original_path;
+        let replacement = &finding.replacement;
+
+        let (message, label, help, applicability) = match finding.lint {
+            StyleLint::RedundantPrefix => (
+                format!("the `**/` prefix of `{}` is redundant", original),
+                format!("same as `{}`", replacement),
+                "a pattern without a `/` in the middle already matches at any depth".to_string(),
+                Applicability::MachineApplicable,
+            ),
+            StyleLint::RedundantSuffix => (
+                format!("the `/**` suffix of `{}` is redundant", original),
+                format!("same as `{}`", replacement),
+                "a directory pattern already covers everything inside the directory".to_string(),
+                Applicability::MachineApplicable,
+            ),
+            StyleLint::SingleStarDirectory => (
+                format!("`{}` only covers files directly inside the directory", original),
+                "nested files are not covered".to_string(),
+                "`*` does not match `/`, so files in subdirectories fall back to earlier rules"
+                    .to_string(),
+                Applicability::MaybeIncorrect,
+            ),
+            StyleLint::NeedlessWildcard => (
+                format!("`{}` matches a single file", original),
+                "wildcard matches one file".to_string(),
+                match &finding.matched {
+                    Some(path) => format!(
+                        "the only match is `{}`; keep the wildcard if it should cover files added later",
+                        path.display()
+                    ),
+                    None => "keep the wildcard if it should cover files added later".to_string(),
+                },
+                Applicability::MaybeIncorrect,
+            ),
+        };
+
+        Diagnostic::warning(finding.lint.code(), message)
+            .with_primary(rule.line_number, Highlight::Pattern, label)
+            .with_help(help)
+            .with_suggestion(
+                format!("write the pattern as `{}`", replacement),
+                replacement.clone(),
+                applicability,
+            )
+    }
+
+    /// Summarises the files no rule matches. Files left unowned on purpose
+    /// by a rule without owners are mentioned but not counted as gaps.
+    pub fn coverage(coverage: &Coverage) -> Self {
+        const EXAMPLES: usize = 5;
+
+        let mut diagnostic = Diagnostic::warning(
+            "coverage",
+            format!(
+                "{} of {} files have no code owners",
+                coverage.unowned.len(),
+                coverage.files
+            ),
+        );
+
+        let mut examples: Vec<String> = coverage
+            .unowned
+            .iter()
+            .take(EXAMPLES)
+            .map(|path| format!("`{}`", path.display()))
+            .collect();
+        if coverage.unowned.len() > EXAMPLES {
+            examples.push(format!("and {} more", coverage.unowned.len() - EXAMPLES));
+        }
+        diagnostic = diagnostic.with_help(format!("unowned: {}", examples.join(", ")));
+
+        if !coverage.explicitly_unowned.is_empty() {
+            diagnostic = diagnostic.with_help(format!(
+                "{} more files are explicitly unowned by rules without owners",
+                coverage.explicitly_unowned.len()
+            ));
+        }
+
+        diagnostic.with_help("add a rule for these paths, or a catch-all `*` rule at the top")
+    }
+}
+
+fn megabytes(bytes: usize) -> String {
+    format!("{:.2} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+impl From<&InvalidLine> for Diagnostic {
+    fn from(line: &InvalidLine) -> Self {
+        let (highlight, label, help) = match &line.reason {
+            InvalidReason::EmptyPattern => (
+                Highlight::Pattern,
+                "this pattern matches nothing",
+                "use `*` to assign default owners for the whole repository",
+            ),
+            InvalidReason::UnclosedCharacterClass { .. } => (
+                Highlight::Columns(line.column..line.column + 1),
+                "this `[` is never closed",
+                "close the character class with `]`, or escape the bracket as `\\[`",
+            ),
+            InvalidReason::UnsupportedNegation => (
+                Highlight::Pattern,
+                "negation is not supported",
+                "later rules override earlier ones; add a more specific rule instead of negating",
+            ),
+            InvalidReason::InvalidGlob { .. } => (
+                Highlight::Pattern,
+                "this pattern is not a valid glob",
+                "check the pattern syntax against the CODEOWNERS documentation",
+            ),
+            InvalidReason::NonUtf8 { byte_offset } => (
+                // The bad sequence shows up as U+FFFD in the decoded line
+                Highlight::Columns(*byte_offset..byte_offset + '\u{FFFD}'.len_utf8()),
+                "not valid UTF-8",
+                "re-encode the file as UTF-8",
+            ),
+            InvalidReason::ByteOrderMark => (
+                Highlight::Columns(0..0),
+                "byte order mark before this line",
+                "save the file as UTF-8 without a byte order mark",
+            ),
+            InvalidReason::CrlfLineEnding => (
+                Highlight::Columns(line.column..line.column),
+                "line ends with `\\r\\n`",
+                "convert the file to LF line endings",
+            ),
+        };
+
+        let (code, message) = match line.reason {
+            InvalidReason::NonUtf8 { .. }
+            | InvalidReason::ByteOrderMark
+            | InvalidReason::CrlfLineEnding => ("encoding", line.reason.to_string()),
+            _ => (
+                "invalid_line",
+                format!("invalid CODEOWNERS line: {}", line.reason),
+            ),
+        };
+
+        let mut diagnostic = Diagnostic::new(line.severity, code, message).with_primary(
+            line.line_number,
+            highlight,
+            label,
+        );
+        if let Some(glob_error) = line.reason.glob_error() {
+            diagnostic = diagnostic.with_help(format!("globset: {}", glob_error));
+        }
+        diagnostic.with_help(help)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(original: &str, line_number: usize) -> CodeOwnerRule {
+        CodeOwnerRule {
+            pattern: original.trim_matches('/').to_string(),
+            original_path: original.to_string(),
+            owners: vec!["@team".to_string()],
+            line_number,
+        }
+    }
+
+    #[test]
+    fn exists_points_at_pattern() {
+        let diagnostic = Diagnostic::exists(&rule("/missing", 4));
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.code, "exists");
+        assert_eq!(diagnostic.line_number(), Some(4));
+        assert_eq!(diagnostic.primary.unwrap().highlight, Highlight::Pattern);
+    }
+
+    #[test]
+    fn duplicate_notes_first_definition() {
+        let first = rule("/docs", 2);
+        let diagnostic = Diagnostic::duplicate_pattern(&rule("docs", 7), Some(&first));
+        assert_eq!(diagnostic.notes.len(), 1);
+        assert_eq!(diagnostic.notes[0].line_number, 2);
+        assert_eq!(diagnostic.notes[0].message, "first defined here");
+        assert!(diagnostic.help[0].contains("normalize"));
+    }
+
+    #[test]
+    fn path_normalization_suggests_the_normalized_pattern() {
+        let finding = PathFinding {
+            rule: rule("./src/", 3),
+            issues: vec![PathIssue::CurrentDir],
+            replacement: Some("/src/".to_string()),
+        };
+        let diagnostic = Diagnostic::path_normalization(&finding);
+        let suggestion = diagnostic.suggestion.unwrap();
+        assert_eq!(suggestion.replacement, "/src/");
+        assert_eq!(suggestion.applicability, Applicability::MachineApplicable);
+        assert_eq!(diagnostic.help, vec![PathIssue::CurrentDir.explanation()]);
+        assert_eq!(diagnostic.severity, Severity::Error);
+
+        // `/builds/…` only looks like a filesystem path
+        let finding = PathFinding {
+            rule: rule("/builds/ci/", 4),
+            issues: vec![PathIssue::AbsolutePath],
+            replacement: None,
+        };
+        assert_eq!(
+            Diagnostic::path_normalization(&finding).severity,
+            Severity::Warning
+        );
+    }
+
+    #[test]
+    fn invalid_line_points_at_unclosed_bracket() {
+        let line = InvalidLine {
+            line_number: 3,
+            content: "docs/[ @team".to_string(),
+            reason: InvalidReason::UnclosedCharacterClass {
+                glob_error: "unclosed character class; missing ']'".to_string(),
+            },
+            column: 5,
+            severity: Severity::Error,
+        };
+        let diagnostic = Diagnostic::from(&line);
+        assert_eq!(
+            diagnostic.message,
+            "invalid CODEOWNERS line: unclosed character class"
+        );
+        assert_eq!(
+            diagnostic.primary.unwrap().highlight,
+            Highlight::Columns(5..6)
+        );
+        assert!(diagnostic.help[0].contains("missing ']'"));
+    }
+
+    #[test]
+    fn encoding_issues_keep_configured_severity() {
+        let line = InvalidLine {
+            line_number: 1,
+            content: "/src/ @a".to_string(),
+            reason: InvalidReason::ByteOrderMark,
+            column: 0,
+            severity: Severity::Warning,
+        };
+        let diagnostic = Diagnostic::from(&line);
+        assert_eq!(diagnostic.severity, Severity::Warning);
+        assert_eq!(diagnostic.code, "encoding");
+    }
+
+    #[test]
+    fn severity_orders_errors_last() {
+        assert!(Severity::Error > Severity::Warning);
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The hosting platform whose CODEOWNERS rules apply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Forge {
+    #[default]
+    GitHub,
+    GitLab,
+}
+
+impl Forge {
+    /// Where the forge looks for a CODEOWNERS file, in order of precedence.
+    /// Only the first file that exists is used.
+    pub fn locations(self) -> &'static [&'static str] {
+        match self {
+            Forge::GitHub => &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"],
+            Forge::GitLab => &["CODEOWNERS", "docs/CODEOWNERS", ".gitlab/CODEOWNERS"],
+        }
+    }
+}
+
+impl fmt::Display for Forge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Forge::GitHub => f.write_str("GitHub"),
+            Forge::GitLab => f.write_str("GitLab"),
+        }
+    }
+}
+
+/// The CODEOWNERS files found in a repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Discovery {
+    /// The file the forge uses, relative to the repository root
+    pub effective: Option<PathBuf>,
+    /// Files that exist but are ignored because `effective` takes precedence
+    pub shadowed: Vec<PathBuf>,
+}
+
+/// Looks for CODEOWNERS files in `repo_root` in the forge's lookup order.
+pub fn discover(repo_root: &Path, forge: Forge) -> Discovery {
+    discover_with(forge, |location| repo_root.join(location).is_file())
+}
+
+/// Like `discover`, with `is_file` deciding whether a location exists, e.g.
+/// in a tree that is not checked out.
+pub fn discover_with(forge: Forge, is_file: impl Fn(&Path) -> bool) -> Discovery {
+    let mut found = forge
+        .locations()
+        .iter()
+        .map(PathBuf::from)
+        .filter(|location| is_file(location));
+
+    Discovery {
+        effective: found.next(),
+        shadowed: found.collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn touch(root: &Path, path: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "* @team\n").unwrap();
+    }
+
+    #[test]
+    fn github_prefers_the_github_directory() {
+        let tmp = tempdir().unwrap();
+        touch(tmp.path(), "docs/CODEOWNERS");
+        touch(tmp.path(), ".github/CODEOWNERS");
+
+        let discovery = discover(tmp.path(), Forge::GitHub);
+        assert_eq!(
+            discovery.effective,
+            Some(PathBuf::from(".github/CODEOWNERS"))
+        );
+        assert_eq!(discovery.shadowed, vec![PathBuf::from("docs/CODEOWNERS")]);
+    }
+
+    #[test]
+    fn gitlab_prefers_the_root() {
+        let tmp = tempdir().unwrap();
+        touch(tmp.path(), "CODEOWNERS");
+        touch(tmp.path(), ".gitlab/CODEOWNERS");
+        touch(tmp.path(), ".github/CODEOWNERS");
+
+        let discovery = discover(tmp.path(), Forge::GitLab);
+        assert_eq!(discovery.effective, Some(PathBuf::from("CODEOWNERS")));
+        assert_eq!(
+            discovery.shadowed,
+            vec![PathBuf::from(".gitlab/CODEOWNERS")]
+        );
+    }
+
+    #[test]
+    fn finds_nothing_in_an_empty_repository() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir(tmp.path().join("CODEOWNERS")).unwrap();
+        assert_eq!(discover(tmp.path(), Forge::GitHub), Discovery::default());
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Failures that prevent a check from producing a trustworthy result.
+///
+/// Findings about the CODEOWNERS file itself are reported as diagnostics,
+/// not errors.
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    InvalidGlob {
+        pattern: String,
+        source: globset::Error,
+    },
+    Walk(ignore::Error),
+    /// Git data that could not be read, e.g. a corrupt index
+    Git {
+        path: PathBuf,
+        message: String,
+    },
+    Config(String),
+}
+
+impl Error {
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn git(path: impl Into<PathBuf>, message: impl Into<String>) -> Self {
+        Error::Git {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::InvalidGlob { pattern, source } => {
+                write!(
+                    f,
+                    "invalid glob for pattern `{}`: {}",
+                    pattern,
+                    source.kind()
+                )
+            }
+            Error::Walk(err) => write!(f, "failed to walk repository: {}", err),
+            Error::Git { path, message } => write!(f, "{}: {}", path.display(), message),
+            Error::Config(message) => write!(f, "invalid configuration: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::InvalidGlob { source, .. } => Some(source),
+            Error::Walk(err) => Some(err),
+            Error::Git { .. } | Error::Config(_) => None,
+        }
+    }
+}
+
+impl From<ignore::Error> for Error {
+    fn from(err: ignore::Error) -> Self {
+        Error::Walk(err)
+    }
+}
@@ -0,0 +1,256 @@
+use crate::error::Result;
+use crate::files::{
+    ignored_flags, list_entries_with, EntryKind, FileOptions, FileSource, RepoEntry,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Parent of the top-level entries
+const ROOT: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Node {
+    parent: u32,
+    /// Index into `FileIndex::names`
+    name: u32,
+    kind: EntryKind,
+    /// Untracked and matched by an ignore pattern
+    ignored: bool,
+}
+
+/// The repository's files and directories, listed once and shared by every
+/// check that looks at them.
+///
+/// Path segments are interned and each entry points at its parent
+/// directory, so an entry costs a few integers however deep it is. Entries
+/// keep the order they were added in, which for `build` is sorted by path.
+#[derive(Debug, Clone, Default)]
+pub struct FileIndex {
+    names: Vec<Arc<OsStr>>,
+    /// Name by segment, sharing the strings in `names`
+    interned: FxHashMap<Arc<OsStr>, u32>,
+    nodes: Vec<Node>,
+    /// Entry by parent and name, for lookups by path
+    children: FxHashMap<(u32, u32), u32>,
+}
+
+impl FileIndex {
+    /// Lists the files from `source` with `options` applied. The working
+    /// tree is read with one parallel walk. Entries from the working tree
+    /// or a manifest also record whether git ignores them; those from the
+    /// index or a revision are tracked, so never ignored.
+    pub fn build(repo_path: &Path, source: FileSource<'_>, options: &FileOptions) -> Result<Self> {
+        let entries = list_entries_with(repo_path, source, options)?;
+        let may_be_ignored = !options.exclude_ignored
+            && matches!(source, FileSource::Worktree | FileSource::Manifest(_));
+        let ignored = match may_be_ignored {
+            true => ignored_flags(repo_path, &entries)?,
+            false => vec![false; entries.len()],
+        };
+        Ok(Self::from_flagged(entries.into_iter().zip(ignored)))
+    }
+
+    /// Indexes `entries`. Directories above an entry that are not listed
+    /// themselves are added.
+    pub fn from_entries(entries: impl IntoIterator<Item = RepoEntry>) -> Self {
+        Self::from_flagged(entries.into_iter().map(|entry| (entry, false)))
+    }
+
+    /// Indexes entries along with whether they are ignored.
+    fn from_flagged(entries: impl IntoIterator<Item = (RepoEntry, bool)>) -> Self {
+        let mut index = FileIndex::default();
+
+        for (entry, ignored) in entries {
+            let mut parent = ROOT;
+            let mut components = entry
+                .path
+                .components()
+                .filter_map(|component| match component {
+                    Component::Normal(name) => Some(name),
+                    _ => None,
+                })
+                .peekable();
+            while let Some(segment) = components.next() {
+                let kind = match components.peek() {
+                    Some(_) => EntryKind::Directory,
+                    None => entry.kind,
+                };
+                let name = match index.interned.get(segment) {
+                    Some(&name) => name,
+                    None => {
+                        let name = index.names.len() as u32;
+                        let segment: Arc<OsStr> = segment.into();
+                        index.names.push(Arc::clone(&segment));
+                        index.interned.insert(segment, name);
+                        name
+                    }
+                };
+                parent = match index.children.get(&(parent, name)) {
+                    Some(&node) => {
+                        if components.peek().is_none() {
+                            index.nodes[node as usize].kind = kind;
+                            index.nodes[node as usize].ignored = ignored;
+                        }
+                        node
+                    }
+                    None => {
+                        let node = index.nodes.len() as u32;
+                        index.nodes.push(Node {
+                            parent,
+                            name,
+                            kind,
+                            ignored: ignored && components.peek().is_none(),
+                        });
+                        index.children.insert((parent, name), node);
+                        node
+                    }
+                };
+            }
+        }
+
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Every entry, in index order.
+    pub fn entries(&self) -> impl Iterator<Item = RepoEntry> + '_ {
+        (0..self.nodes.len()).map(|node| RepoEntry {
+            path: self.path(node as u32),
+            kind: self.nodes[node].kind,
+        })
+    }
+
+    /// What is at `path`, relative to the repository root.
+    pub fn kind(&self, path: &Path) -> Option<EntryKind> {
+        let mut node = ROOT;
+        for component in path.components() {
+            let Component::Normal(segment) = component else {
+                continue;
+            };
+            let name = *self.interned.get(segment)?;
+            node = *self.children.get(&(node, name))?;
+        }
+        (node != ROOT).then(|| self.nodes[node as usize].kind)
+    }
+
+    /// Every file, with whether git ignores it.
+    pub fn files_with_ignored(&self) -> impl Iterator<Item = (PathBuf, bool)> + '_ {
+        (0..self.nodes.len() as u32)
+            .filter(|&node| self.nodes[node as usize].kind == EntryKind::File)
+            .map(|node| (self.path(node), self.nodes[node as usize].ignored))
+    }
+
+    /// Whether any entry is ignored.
+    pub fn has_ignored(&self) -> bool {
+        self.nodes.iter().any(|node| node.ignored)
+    }
+
+    /// The paths of the submodules.
+    pub fn submodules(&self) -> FxHashSet<PathBuf> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.kind == EntryKind::Submodule)
+            .map(|(node, _)| self.path(node as u32))
+            .collect()
+    }
+
+    /// The paths that need an owner: files, and each submodule as a whole
+    /// since changing its commit is a change to the parent. Files inside
+    /// submodules are left out, as the submodule's own CODEOWNERS covers them.
+    pub fn ownable_paths(&self) -> Vec<PathBuf> {
+        (0..self.nodes.len() as u32)
+            .filter(|&node| self.nodes[node as usize].kind != EntryKind::Directory)
+            .filter(|&node| !self.in_submodule(node))
+            .map(|node| self.path(node))
+            .collect()
+    }
+
+    fn in_submodule(&self, mut node: u32) -> bool {
+        loop {
+            node = self.nodes[node as usize].parent;
+            if node == ROOT {
+                return false;
+            }
+            if self.nodes[node as usize].kind == EntryKind::Submodule {
+                return true;
+            }
+        }
+    }
+
+    fn path(&self, node: u32) -> PathBuf {
+        let mut segments = Vec::new();
+        let mut current = node;
+        while current != ROOT {
+            let node = self.nodes[current as usize];
+            segments.push(&*self.names[node.name as usize]);
+            current = node.parent;
+        }
+        segments.iter().rev().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, kind: EntryKind) -> RepoEntry {
+        RepoEntry {
+            path: PathBuf::from(path),
+            kind,
+        }
+    }
+
+    #[test]
+    fn interns_segments_and_adds_parent_directories() {
+        let index = FileIndex::from_entries([
+            entry("src/lib.rs", EntryKind::File),
+            entry("src/bin/lib.rs", EntryKind::File),
+            entry("vendor/lib", EntryKind::Submodule),
+            entry("vendor/lib/src/lib.rs", EntryKind::File),
+        ]);
+
+        // `lib.rs` and `src` are stored once each
+        assert_eq!(index.names.len(), 5);
+        assert_eq!(
+            index.entries().collect::<Vec<_>>(),
+            vec![
+                entry("src", EntryKind::Directory),
+                entry("src/lib.rs", EntryKind::File),
+                entry("src/bin", EntryKind::Directory),
+                entry("src/bin/lib.rs", EntryKind::File),
+                entry("vendor", EntryKind::Directory),
+                entry("vendor/lib", EntryKind::Submodule),
+                entry("vendor/lib/src", EntryKind::Directory),
+                entry("vendor/lib/src/lib.rs", EntryKind::File),
+            ]
+        );
+        assert_eq!(index.kind(Path::new("src/bin")), Some(EntryKind::Directory));
+        assert_eq!(
+            index.kind(Path::new("src/bin/lib.rs")),
+            Some(EntryKind::File)
+        );
+        assert_eq!(index.kind(Path::new("src/main.rs")), None);
+        assert_eq!(
+            index.ownable_paths(),
+            vec![
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("src/bin/lib.rs"),
+                PathBuf::from("vendor/lib"),
+            ]
+        );
+        assert_eq!(
+            index.submodules(),
+            FxHashSet::from_iter([PathBuf::from("vendor/lib")])
+        );
+    }
+}
@@ -0,0 +1,837 @@
+use crate::error::{Error, Result};
+use crate::git::git_dir;
+use crate::git::index::{read_index, IndexEntryKind};
+use crate::git::object::ObjectId;
+use crate::git::repository::{Repository, TreeEntryKind};
+use crate::git::submodule::read_gitmodules;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{DirEntry, Match, WalkBuilder, WalkState};
+use parking_lot::Mutex;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::{env, io};
+
+/// Where the list of repository files comes from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileSource<'a> {
+    /// Files tracked in the git index, which is what the forge sees
+    #[default]
+    GitIndex,
+    /// Everything on disk, including untracked and ignored files
+    Worktree,
+    /// Files in a tree from the object database, which does not need to
+    /// be checked out
+    Revision(ObjectId),
+    /// A list of files given up front, e.g. for a tree too large to check out
+    Manifest(&'a Manifest),
+}
+
+/// Relative file paths read from a manifest rather than the repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    entries: Vec<RepoEntry>,
+}
+
+impl Manifest {
+    /// Parses paths separated by newlines, or by NULs when there are any,
+    /// as `git ls-files -z` writes them. A trailing `/` marks a directory.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let separator = if bytes.contains(&0) { 0 } else { b'\n' };
+        let mut entries = Vec::new();
+        for (idx, line) in bytes.split(|&byte| byte == separator).enumerate() {
+            let line = match separator {
+                b'\n' => line.strip_suffix(b"\r").unwrap_or(line),
+                _ => line,
+            };
+            if line.is_empty() {
+                continue;
+            }
+            let invalid =
+                |reason: &str| Error::Config(format!("file list entry {}: {}", idx + 1, reason));
+            let path = std::str::from_utf8(line).map_err(|_| invalid("not valid UTF-8"))?;
+
+            let mut path = path;
+            while let Some(rest) = path.strip_prefix("./") {
+                path = rest;
+            }
+            let (path, kind) = match path.strip_suffix('/') {
+                Some(dir) => (dir, EntryKind::Directory),
+                None => (path, EntryKind::File),
+            };
+            let outside = path.starts_with('/')
+                || path
+                    .split('/')
+                    .any(|segment| segment.is_empty() || segment == "..");
+            if outside {
+                return Err(invalid(&format!(
+                    "`{}` is not a relative path inside the repository",
+                    String::from_utf8_lossy(line)
+                )));
+            }
+            entries.push(RepoEntry {
+                path: PathBuf::from(path),
+                kind,
+            });
+        }
+        Ok(Manifest { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryKind {
+    /// A regular file or a symlink, which the forge treats as a file
+    File,
+    Directory,
+    /// A submodule checkout, recorded as a single commit
+    Submodule,
+}
+
+/// A path in the repository, relative to its root.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RepoEntry {
+    pub path: PathBuf,
+    pub kind: EntryKind,
+}
+
+/// Filters that apply whichever source the files come from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileOptions {
+    /// Leave out untracked files and directories that `.gitignore` ignores.
+    /// Only the worktree and manifest sources can have any.
+    pub exclude_ignored: bool,
+    /// Globs for paths to leave out, e.g. `vendor` or `bazel-out`. See
+    /// `Excludes` for how they match.
+    pub exclude: Vec<String>,
+    pub symlinks: SymlinkPolicy,
+    /// List the files of checked-out submodules too. Otherwise a submodule
+    /// is a single opaque entry, which is all the forge sees of it.
+    pub descend_submodules: bool,
+}
+
+impl FileOptions {
+    pub const DEFAULT: &'static FileOptions = &FileOptions {
+        exclude_ignored: false,
+        exclude: Vec::new(),
+        symlinks: SymlinkPolicy::Entry,
+        descend_submodules: false,
+    };
+}
+
+/// How symlinks count when looking for files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// A symlink is a file of its own, as the forge sees it, whether or not
+    /// its target exists
+    #[default]
+    Entry,
+    /// Symlinks in the working tree are followed, so a link to a directory
+    /// is walked and a broken link does not exist. The git index and
+    /// revisions have no targets to follow and treat links as `Entry`.
+    Follow,
+    /// Symlinks are left out as if they were not there
+    Skip,
+}
+
+/// Compiled exclude globs.
+///
+/// A glob without a `/` matches a name at any depth, like `node_modules`.
+/// One with a `/` is anchored at the repository root, and a leading `/`
+/// anchors a plain name. A trailing `/` is ignored, and excluding a
+/// directory excludes everything inside it.
+#[derive(Debug, Clone)]
+pub struct Excludes {
+    set: GlobSet,
+}
+
+impl Excludes {
+    pub fn new(globs: &[String]) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for glob in globs {
+            let invalid = |reason: &dyn std::fmt::Display| {
+                Error::Config(format!("invalid exclude glob `{}`: {}", glob, reason))
+            };
+            let trimmed = glob.strip_suffix('/').unwrap_or(glob);
+            let anchored = match trimmed.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if trimmed.contains('/') => trimmed.to_string(),
+                None => format!("**/{}", trimmed),
+            };
+            if anchored.is_empty() || anchored == "**/" {
+                return Err(invalid(&"it is empty"));
+            }
+            let compiled = GlobBuilder::new(&anchored)
+                .literal_separator(true)
+                .build()
+                .map_err(|err| invalid(err.kind()))?;
+            builder.add(compiled);
+        }
+        let set = builder
+            .build()
+            .map_err(|err| Error::Config(format!("invalid exclude globs: {}", err.kind())))?;
+        Ok(Excludes { set })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Whether `path`, relative to the repository root, or a directory
+    /// above it is excluded.
+    pub fn contains(&self, path: &Path) -> bool {
+        !self.is_empty()
+            && path
+                .ancestors()
+                .take_while(|ancestor| !ancestor.as_os_str().is_empty())
+                .any(|ancestor| self.set.is_match(ancestor))
+    }
+
+    /// Whether `path` itself is excluded. Walks use this to prune excluded
+    /// directories, so their contents never need checking.
+    fn is_match(&self, path: &Path) -> bool {
+        !self.is_empty() && self.set.is_match(path)
+    }
+}
+
+/// The submodule paths declared in the working tree's `.gitmodules`.
+pub fn worktree_submodules(repo_path: &Path) -> Result<FxHashSet<PathBuf>> {
+    Ok(read_gitmodules(repo_path)?
+        .into_iter()
+        .map(|submodule| submodule.path)
+        .collect())
+}
+
+/// The submodule among `submodules` that `path` is strictly inside of.
+pub fn containing_submodule<'a>(
+    path: &Path,
+    submodules: &'a FxHashSet<PathBuf>,
+) -> Option<&'a Path> {
+    path.ancestors()
+        .skip(1)
+        .take_while(|ancestor| !ancestor.as_os_str().is_empty())
+        .find_map(|ancestor| submodules.get(ancestor))
+        .map(PathBuf::as_path)
+}
+
+/// Walks the working tree in parallel, skipping `.git` and applying
+/// `options` apart from `exclude_ignored`. Submodules are not descended
+/// into unless `options` asks for it.
+///
+/// Each walker thread gets a visitor from `make_visitor`, which is called
+/// with every entry below the root, relative to it. What the visitor
+/// returns decides whether the walk descends into a directory, skips it or
+/// stops. An unreadable entry does not stop the walk; the first one is
+/// returned as an error once it is done.
+pub(crate) fn walk_worktree<'s, V>(
+    repo_path: &'s Path,
+    options: &FileOptions,
+    excludes: &Excludes,
+    submodules: &FxHashSet<PathBuf>,
+    threads: usize,
+    mut make_visitor: impl FnMut() -> V,
+) -> Result<()>
+where
+    V: FnMut(&Path, EntryKind) -> WalkState + Send + 's,
+{
+    let root = repo_path.to_path_buf();
+    let excludes = excludes.clone();
+    let skip_links = options.symlinks == SymlinkPolicy::Skip;
+    let follow_links = options.symlinks == SymlinkPolicy::Follow;
+    let opaque = match options.descend_submodules {
+        true => FxHashSet::default(),
+        false => submodules.clone(),
+    };
+    let walk_error: Mutex<Option<ignore::Error>> = Mutex::new(None);
+
+    WalkBuilder::new(repo_path)
+        .standard_filters(false)
+        .hidden(false)
+        .follow_links(follow_links)
+        .filter_entry(move |entry| {
+            // Submodules and linked worktrees have a `.git` file instead
+            if entry.file_name() == ".git" {
+                return false;
+            }
+            if skip_links && entry.path_is_symlink() {
+                return false;
+            }
+            let Ok(rel_path) = entry.path().strip_prefix(&root) else {
+                return true;
+            };
+            let in_submodule = rel_path
+                .parent()
+                .is_some_and(|parent| opaque.contains(parent));
+            !in_submodule && !excludes.is_match(rel_path)
+        })
+        .threads(threads)
+        .build_parallel()
+        .run(|| {
+            let mut visit = make_visitor();
+            let walk_error = &walk_error;
+            Box::new(move |entry: Result<DirEntry, ignore::Error>| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(err) if follow_links && is_unfollowable_link(&err) => {
+                        return WalkState::Continue
+                    }
+                    Err(err) => {
+                        walk_error.lock().get_or_insert(err);
+                        return WalkState::Continue;
+                    }
+                };
+                let kind = if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                    EntryKind::Directory
+                } else {
+                    EntryKind::File
+                };
+                match entry.path().strip_prefix(repo_path) {
+                    Ok(rel_path) if !rel_path.as_os_str().is_empty() => visit(rel_path, kind),
+                    _ => WalkState::Continue,
+                }
+            })
+        });
+
+    match walk_error.into_inner() {
+        Some(err) => Err(Error::Walk(err)),
+        None => Ok(()),
+    }
+}
+
+/// Whether a walk error only means a symlink could not be followed, because
+/// it is broken or loops. Those links do not exist under
+/// `SymlinkPolicy::Follow`.
+fn is_unfollowable_link(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. } => {
+            is_unfollowable_link(err)
+        }
+        _ => err
+            .io_error()
+            .is_some_and(|err| err.kind() == io::ErrorKind::NotFound),
+    }
+}
+
+/// Lists every file in the working tree, relative to `repo_path`.
+///
+/// Nothing is filtered except the `.git` directory, and the result is
+/// sorted so reports are stable.
+pub fn list_files(repo_path: &Path) -> Result<Vec<PathBuf>> {
+    Ok(list_entries(repo_path, FileSource::Worktree)?
+        .into_iter()
+        .filter(|entry| entry.kind == EntryKind::File)
+        .map(|entry| entry.path)
+        .collect())
+}
+
+/// Lists the files and directories in the repository, sorted by path.
+///
+/// The git index and manifests only record files, so their directories
+/// are the parents of the files.
+pub fn list_entries(repo_path: &Path, source: FileSource) -> Result<Vec<RepoEntry>> {
+    list_entries_with(repo_path, source, FileOptions::DEFAULT)
+}
+
+/// Like `list_entries`, with `options` applied.
+pub fn list_entries_with(
+    repo_path: &Path,
+    source: FileSource,
+    options: &FileOptions,
+) -> Result<Vec<RepoEntry>> {
+    let excludes = Excludes::new(&options.exclude)?;
+    let skip_links = options.symlinks == SymlinkPolicy::Skip;
+    let mut entries = match source {
+        FileSource::GitIndex => with_parent_directories(index_entries(
+            repo_path,
+            skip_links,
+            options.descend_submodules,
+        )?),
+        FileSource::Worktree => {
+            let mut submodules = worktree_submodules(repo_path)?;
+            submodules.retain(|path| !excludes.contains(path));
+            let entries = worktree_entries(repo_path, options, &excludes, &submodules)?;
+            with_parent_directories(with_submodules(entries, &submodules))
+        }
+        FileSource::Revision(tree) => tree_entries(repo_path, &tree, skip_links)?,
+        FileSource::Manifest(manifest) => {
+            // The manifest has no file modes, so submodules come from disk
+            let submodules = worktree_submodules(repo_path)?;
+            with_parent_directories(with_submodules(manifest.entries.clone(), &submodules))
+        }
+    };
+    if !options.descend_submodules && matches!(source, FileSource::Manifest(_)) {
+        let submodules = submodules_in(&entries);
+        entries.retain(|entry| containing_submodule(&entry.path, &submodules).is_none());
+    }
+    if source != FileSource::Worktree {
+        entries.retain(|entry| !excludes.contains(&entry.path));
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries.dedup();
+    // Everything in the index or a revision is tracked, so nothing there
+    // counts as ignored
+    if options.exclude_ignored && matches!(source, FileSource::Worktree | FileSource::Manifest(_)) {
+        let ignored = ignored_flags(repo_path, &entries)?;
+        let mut ignored = ignored.into_iter();
+        entries.retain(|_| !ignored.next().unwrap_or(false));
+    }
+    Ok(entries)
+}
+
+/// Which of `entries` git ignores: a `.gitignore` among the entries,
+/// `.git/info/exclude` or the global excludes file matches them, directly
+/// or through a parent directory, and they are not tracked. A tracked file
+/// shows up in every change to it whatever the patterns say. `entries`
+/// must be sorted by path.
+pub(crate) fn ignored_flags(repo_path: &Path, entries: &[RepoEntry]) -> Result<Vec<bool>> {
+    let tracked = tracked_paths(repo_path)?;
+    let matchers = IgnoreMatchers::new(repo_path, entries);
+    // Parents sort before their contents, so they are always decided first
+    let mut ignored_dirs: FxHashSet<&Path> = FxHashSet::default();
+    Ok(entries
+        .iter()
+        .map(|entry| {
+            let is_dir = entry.kind != EntryKind::File;
+            let gitignored = entry
+                .path
+                .parent()
+                .is_some_and(|parent| ignored_dirs.contains(parent))
+                || matchers.is_ignored(&entry.path, is_dir);
+            if gitignored && is_dir {
+                ignored_dirs.insert(&entry.path);
+            }
+            gitignored && !tracked.contains(&entry.path)
+        })
+        .collect())
+}
+
+/// Paths in the git index, with every directory above them. A directory
+/// with tracked files is tracked too, even if a pattern ignores it.
+fn tracked_paths(repo_path: &Path) -> Result<FxHashSet<PathBuf>> {
+    // A checkout without `.git`, e.g. from an archive, tracks nothing
+    if !repo_path.join(".git").exists() {
+        return Ok(FxHashSet::default());
+    }
+    let mut tracked = FxHashSet::default();
+    for entry in index_entries(repo_path, false, true)? {
+        for path in entry.path.ancestors() {
+            if path.as_os_str().is_empty() || !tracked.insert(path.to_path_buf()) {
+                break;
+            }
+        }
+    }
+    Ok(tracked)
+}
+
+/// The ignore patterns that apply in a repository.
+struct IgnoreMatchers {
+    repo_path: PathBuf,
+    /// `.gitignore` files by the directory they are in
+    directories: FxHashMap<PathBuf, Gitignore>,
+    exclude: Gitignore,
+    global: Gitignore,
+}
+
+impl IgnoreMatchers {
+    fn new(repo_path: &Path, entries: &[RepoEntry]) -> Self {
+        // git skips patterns it cannot parse and files it cannot read, so
+        // errors from the builders are dropped the same way
+        let build = |root: &Path, file: PathBuf| {
+            let mut builder = GitignoreBuilder::new(root);
+            let _ = builder.add(file);
+            builder.build().unwrap_or_else(|_| Gitignore::empty())
+        };
+        let directories = entries
+            .iter()
+            .filter(|entry| entry.kind == EntryKind::File)
+            .filter(|entry| entry.path.file_name() == Some(OsStr::new(".gitignore")))
+            .map(|entry| {
+                let dir = entry.path.parent().unwrap_or(Path::new(""));
+                let matcher = build(&repo_path.join(dir), repo_path.join(&entry.path));
+                (dir.to_path_buf(), matcher)
+            })
+            .collect();
+        let exclude = match git_dir(repo_path) {
+            Ok(git_dir) => build(repo_path, git_dir.join("info/exclude")),
+            Err(_) => Gitignore::empty(),
+        };
+        IgnoreMatchers {
+            repo_path: repo_path.to_path_buf(),
+            directories,
+            exclude,
+            global: Gitignore::global().0,
+        }
+    }
+
+    /// Whether the patterns ignore `path` itself. The closest `.gitignore`
+    /// decides, then `.git/info/exclude`, then the global excludes file.
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let absolute = self.repo_path.join(path);
+        let matches = path
+            .ancestors()
+            .skip(1)
+            .filter_map(|dir| self.directories.get(dir))
+            .chain([&self.exclude])
+            .map(|matcher| matcher.matched(&absolute, is_dir))
+            // The global file is not tied to a directory
+            .chain([self.global.matched(path, is_dir)]);
+        for matched in matches {
+            match matched {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+}
+
+/// The paths of the submodule entries.
+pub fn submodules_in(entries: &[RepoEntry]) -> FxHashSet<PathBuf> {
+    entries
+        .iter()
+        .filter(|entry| entry.kind == EntryKind::Submodule)
+        .map(|entry| entry.path.clone())
+        .collect()
+}
+
+/// Marks the entries at submodule paths as submodules, and adds the
+/// submodules that are not checked out.
+fn with_submodules(mut entries: Vec<RepoEntry>, submodules: &FxHashSet<PathBuf>) -> Vec<RepoEntry> {
+    let mut missing = submodules.clone();
+    for entry in &mut entries {
+        if missing.remove(&entry.path) {
+            entry.kind = EntryKind::Submodule;
+        }
+    }
+    entries.extend(missing.into_iter().map(|path| RepoEntry {
+        path,
+        kind: EntryKind::Submodule,
+    }));
+    entries
+}
+
+/// Adds an entry for every directory above the listed entries.
+fn with_parent_directories(mut entries: Vec<RepoEntry>) -> Vec<RepoEntry> {
+    let mut directories: FxHashSet<PathBuf> = entries
+        .iter()
+        .filter(|entry| entry.kind != EntryKind::File)
+        .map(|entry| entry.path.clone())
+        .collect();
+
+    let mut parents = Vec::new();
+    for entry in &entries {
+        for parent in entry.path.ancestors().skip(1) {
+            if parent.as_os_str().is_empty() || !directories.insert(parent.to_path_buf()) {
+                break;
+            }
+            parents.push(RepoEntry {
+                path: parent.to_path_buf(),
+                kind: EntryKind::Directory,
+            });
+        }
+    }
+    entries.extend(parents);
+    entries
+}
+
+/// The index entries, and with `descend` those of checked-out submodules,
+/// prefixed with the submodule path.
+fn index_entries(repo_path: &Path, skip_links: bool, descend: bool) -> Result<Vec<RepoEntry>> {
+    let mut entries = Vec::new();
+    for entry in read_index(repo_path)? {
+        if skip_links && entry.kind == IndexEntryKind::Symlink {
+            continue;
+        }
+        let path = PathBuf::from(entry.path);
+        let kind = match entry.kind {
+            IndexEntryKind::File | IndexEntryKind::Symlink => EntryKind::File,
+            IndexEntryKind::Gitlink => EntryKind::Submodule,
+            IndexEntryKind::SparseDirectory => EntryKind::Directory,
+        };
+        if descend && kind == EntryKind::Submodule {
+            let checkout = repo_path.join(&path);
+            // An uninitialized submodule is an empty directory
+            if checkout.join(".git").exists() {
+                entries.extend(
+                    index_entries(&checkout, skip_links, descend)?
+                        .into_iter()
+                        .map(|inner| RepoEntry {
+                            path: path.join(inner.path),
+                            kind: inner.kind,
+                        }),
+                );
+            }
+        }
+        entries.push(RepoEntry { path, kind });
+    }
+    Ok(entries)
+}
+
+fn tree_entries(repo_path: &Path, tree: &ObjectId, skip_links: bool) -> Result<Vec<RepoEntry>> {
+    let repo = Repository::open(repo_path)?;
+    let entries = repo
+        .list_tree(tree)?
+        .into_iter()
+        .filter(|entry| !(skip_links && entry.kind == TreeEntryKind::Symlink))
+        .map(|entry| RepoEntry {
+            path: PathBuf::from(entry.path),
+            kind: match entry.kind {
+                TreeEntryKind::File | TreeEntryKind::Symlink => EntryKind::File,
+                TreeEntryKind::Directory => EntryKind::Directory,
+                TreeEntryKind::Gitlink => EntryKind::Submodule,
+            },
+        })
+        .collect();
+    Ok(entries)
+}
+
+fn worktree_entries(
+    repo_path: &Path,
+    options: &FileOptions,
+    excludes: &Excludes,
+    submodules: &FxHashSet<PathBuf>,
+) -> Result<Vec<RepoEntry>> {
+    let entries: Mutex<Vec<RepoEntry>> = Mutex::new(Vec::new());
+    walk_worktree(
+        repo_path,
+        options,
+        excludes,
+        submodules,
+        walk_threads(num_cpus::get().min(8))?,
+        || {
+            // Each thread collects on its own and hands over when it is done
+            let mut collector = Collector {
+                local: Vec::new(),
+                shared: &entries,
+            };
+            move |rel_path: &Path, kind| {
+                collector.local.push(RepoEntry {
+                    path: rel_path.to_path_buf(),
+                    kind,
+                });
+                WalkState::Continue
+            }
+        },
+    )?;
+    Ok(entries.into_inner())
+}
+
+/// Entries found by one walker thread, added to the shared list when the
+/// thread finishes.
+struct Collector<'a> {
+    local: Vec<RepoEntry>,
+    shared: &'a Mutex<Vec<RepoEntry>>,
+}
+
+impl Drop for Collector<'_> {
+    fn drop(&mut self) {
+        self.shared.lock().append(&mut self.local);
+    }
+}
+
+/// The walker thread count from `CODEOWNERS_THREADS`, or `default` when it
+/// is not set.
+pub(crate) fn walk_threads(default: usize) -> Result<usize> {
+    let Some(value) = env::var_os("CODEOWNERS_THREADS").filter(|value| !value.is_empty()) else {
+        return Ok(default);
+    };
+    match value.to_str().and_then(|value| value.trim().parse().ok()) {
+        Some(threads) if threads > 0 => Ok(threads),
+        _ => Err(Error::Config(format!(
+            "CODEOWNERS_THREADS must be a positive number, got {:?}",
+            value
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::tests::git;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn git_index_lists_tracked_files_and_their_directories() {
+        let tmp = tempdir().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        fs::create_dir_all(tmp.path().join("src/nested")).unwrap();
+        fs::create_dir_all(tmp.path().join("target/debug")).unwrap();
+        fs::write(tmp.path().join("src/nested/lib.rs"), "").unwrap();
+        fs::write(tmp.path().join("target/debug/app"), "").unwrap();
+        git(tmp.path(), &["add", "src"]);
+
+        let entries = list_entries(tmp.path(), FileSource::GitIndex).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                RepoEntry {
+                    path: PathBuf::from("src"),
+                    kind: EntryKind::Directory
+                },
+                RepoEntry {
+                    path: PathBuf::from("src/nested"),
+                    kind: EntryKind::Directory
+                },
+                RepoEntry {
+                    path: PathBuf::from("src/nested/lib.rs"),
+                    kind: EntryKind::File
+                },
+            ]
+        );
+
+        let worktree = list_entries(tmp.path(), FileSource::Worktree).unwrap();
+        assert!(worktree
+            .iter()
+            .any(|entry| entry.path == Path::new("target/debug/app")));
+        assert!(!worktree.iter().any(|entry| entry.path.starts_with(".git")));
+    }
+
+    #[test]
+    fn revision_lists_the_committed_tree() {
+        let tmp = tempdir().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/lib.rs"), "").unwrap();
+        git(tmp.path(), &["add", "."]);
+        git(tmp.path(), &["commit", "-q", "-m", "first"]);
+        // Neither staged nor on disk changes affect the revision
+        fs::remove_file(tmp.path().join("src/lib.rs")).unwrap();
+        fs::write(tmp.path().join("new.rs"), "").unwrap();
+        git(tmp.path(), &["add", "-A"]);
+
+        let tree = Repository::open(tmp.path())
+            .unwrap()
+            .resolve_tree("HEAD")
+            .unwrap();
+        let paths: Vec<PathBuf> = list_entries(tmp.path(), FileSource::Revision(tree))
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("src"), PathBuf::from("src/lib.rs")]
+        );
+    }
+
+    #[test]
+    fn manifest_lists_its_files_and_their_directories() {
+        let manifest = Manifest::parse(b"./src/lib.rs\r\nsrc/nested/mod.rs\n\nassets/\n").unwrap();
+        let entries = list_entries(Path::new("/nonexistent"), FileSource::Manifest(&manifest))
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.path.to_string_lossy().into_owned(), entry.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                ("assets".to_string(), EntryKind::Directory),
+                ("src".to_string(), EntryKind::Directory),
+                ("src/lib.rs".to_string(), EntryKind::File),
+                ("src/nested".to_string(), EntryKind::Directory),
+                ("src/nested/mod.rs".to_string(), EntryKind::File),
+            ]
+        );
+
+        let nul_separated = Manifest::parse(b"a b.txt\0c\nd.txt\0").unwrap();
+        assert_eq!(nul_separated.len(), 2);
+        for invalid in [&b"/etc/passwd"[..], b"src/../../x", b"src//lib.rs", b"\xff"] {
+            assert!(
+                matches!(Manifest::parse(invalid), Err(Error::Config(_))),
+                "{:?}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn excludes_ignored_files_that_are_not_tracked() {
+        let tmp = tempdir().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        fs::create_dir_all(tmp.path().join("dist")).unwrap();
+        fs::write(tmp.path().join(".gitignore"), "dist/\n*.log\n").unwrap();
+        fs::write(tmp.path().join("dist/app.js"), "").unwrap();
+        fs::write(tmp.path().join("debug.log"), "").unwrap();
+        fs::write(tmp.path().join("lib.rs"), "").unwrap();
+        // Force-added, so tracked even though they are ignored
+        git(tmp.path(), &["add", "-f", "."]);
+        fs::write(tmp.path().join("dist/bundle.js"), "").unwrap();
+        fs::write(tmp.path().join("trace.log"), "").unwrap();
+
+        let options = FileOptions {
+            exclude_ignored: true,
+            ..FileOptions::default()
+        };
+        let paths = |source| -> Vec<PathBuf> {
+            list_entries_with(tmp.path(), source, &options)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.path)
+                .collect()
+        };
+        let tracked: Vec<PathBuf> = [".gitignore", "debug.log", "dist", "dist/app.js", "lib.rs"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(paths(FileSource::Worktree), tracked);
+        assert_eq!(paths(FileSource::GitIndex), tracked);
+
+        let entries = list_entries(tmp.path(), FileSource::Worktree).unwrap();
+        let ignored: Vec<&Path> = entries
+            .iter()
+            .zip(ignored_flags(tmp.path(), &entries).unwrap())
+            .filter(|(_, ignored)| *ignored)
+            .map(|(entry, _)| entry.path.as_path())
+            .collect();
+        assert_eq!(
+            ignored,
+            [Path::new("dist/bundle.js"), Path::new("trace.log")]
+        );
+    }
+
+    #[test]
+    fn excludes_match_names_anywhere_and_paths_from_the_root() {
+        let excludes = Excludes::new(&[
+            "node_modules".to_string(),
+            "/vendor/".to_string(),
+            "bazel-*".to_string(),
+            "docs/generated".to_string(),
+        ])
+        .unwrap();
+        for excluded in [
+            "node_modules",
+            "web/node_modules/pkg/index.js",
+            "vendor/lib.go",
+            "bazel-out/k8/bin",
+            "docs/generated/api.md",
+        ] {
+            assert!(excludes.contains(Path::new(excluded)), "{}", excluded);
+        }
+        for kept in [
+            "src/vendor/lib.go",
+            "web/docs/generated/api.md",
+            "node_modules.md",
+        ] {
+            assert!(!excludes.contains(Path::new(kept)), "{}", kept);
+        }
+        assert!(matches!(
+            Excludes::new(&["/".to_string()]),
+            Err(Error::Config(_))
+        ));
+        assert!(matches!(
+            Excludes::new(&["src/[".to_string()]),
+            Err(Error::Config(_))
+        ));
+    }
+}
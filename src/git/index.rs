@@ -0,0 +1,319 @@
+use crate::error::{Error, Result};
+use crate::git::{git_dir, hash_len};
+use std::fs;
+use std::path::Path;
+
+const SIGNATURE: &[u8] = b"DIRC";
+
+// Entry flags
+const EXTENDED: u16 = 0x4000;
+const STAGE_MASK: u16 = 0x3000;
+
+// Entry modes
+const MODE_TYPE_MASK: u32 = 0o170000;
+const MODE_GITLINK: u32 = 0o160000;
+const MODE_SYMLINK: u32 = 0o120000;
+const MODE_TREE: u32 = 0o040000;
+
+/// The smallest an entry can be on disk: fixed fields, a one-byte path and
+/// its padding. Bounds what the header's entry count may reserve.
+const MIN_ENTRY_SIZE: usize = 62;
+
+/// What an index entry records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexEntryKind {
+    File,
+    Symlink,
+    /// A submodule commit
+    Gitlink,
+    /// A directory collapsed by a sparse index; its files are not listed
+    SparseDirectory,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Slash-separated path relative to the repository root. Bytes that
+    /// are not valid UTF-8 are replaced.
+    pub path: String,
+    pub kind: IndexEntryKind,
+}
+
+/// Reads the index of the work tree at `repo_root`. Each path is listed
+/// once, even when a merge conflict leaves several stages of it.
+pub fn read_index(repo_root: &Path) -> Result<Vec<IndexEntry>> {
+    let git_dir = git_dir(repo_root)?;
+    let path = git_dir.join("index");
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        // A fresh repository has no index until something is staged
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(Error::io(path, err)),
+    };
+    parse_index(&bytes, hash_len(&git_dir)).map_err(|message| Error::git(path, message))
+}
+
+/// Parses index versions 2, 3 and 4. `hash_len` is 20 for SHA-1
+/// repositories and 32 for SHA-256 ones.
+pub fn parse_index(bytes: &[u8], hash_len: usize) -> Result<Vec<IndexEntry>, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+
+    if reader.take(4)? != SIGNATURE {
+        return Err("not a git index file".to_string());
+    }
+    let version = reader.u32()?;
+    if !(2..=4).contains(&version) {
+        return Err(format!("unsupported index version {}", version));
+    }
+    let count = reader.u32()? as usize;
+
+    // The count comes from the file, so only reserve what the bytes can hold
+    let mut entries: Vec<IndexEntry> = Vec::with_capacity(count.min(bytes.len() / MIN_ENTRY_SIZE));
+    let mut previous_path: Vec<u8> = Vec::new();
+    for _ in 0..count {
+        let start = reader.pos;
+        // ctime, mtime, dev, ino
+        reader.take(24)?;
+        let mode = reader.u32()?;
+        // uid, gid, size, object id
+        reader.take(12 + hash_len)?;
+        let flags = reader.u16()?;
+        if flags & EXTENDED != 0 {
+            if version < 3 {
+                return Err("extended entry flags in a version 2 index".to_string());
+            }
+            reader.u16()?;
+        }
+
+        let path = if version == 4 {
+            // The path is stored as a suffix of the previous entry's path
+            let strip = reader.varint()?;
+            let keep = previous_path
+                .len()
+                .checked_sub(strip)
+                .ok_or("path prefix longer than the previous path")?;
+            previous_path.truncate(keep);
+            previous_path.extend_from_slice(reader.until_nul()?);
+            previous_path.clone()
+        } else {
+            let path = reader.until_nul()?.to_vec();
+            // Entries are NUL-padded to a multiple of eight bytes
+            let len = reader.pos - start;
+            reader.take((8 - len % 8) % 8)?;
+            path
+        };
+
+        let kind = match mode & MODE_TYPE_MASK {
+            MODE_GITLINK => IndexEntryKind::Gitlink,
+            MODE_SYMLINK => IndexEntryKind::Symlink,
+            MODE_TREE => IndexEntryKind::SparseDirectory,
+            _ => IndexEntryKind::File,
+        };
+        let path = String::from_utf8_lossy(&path);
+        let path = path.strip_suffix('/').unwrap_or(&path).to_string();
+
+        // Entries are sorted by path, so conflict stages are adjacent
+        let is_conflict_stage = flags & STAGE_MASK != 0;
+        if is_conflict_stage && entries.last().is_some_and(|last| last.path == path) {
+            continue;
+        }
+        entries.push(IndexEntry { path, kind });
+    }
+
+    if has_extension(&reader.bytes[reader.pos..], b"link", hash_len) {
+        return Err("split indexes are not supported".to_string());
+    }
+
+    Ok(entries)
+}
+
+/// Whether the extensions after the entries include `signature`.
+fn has_extension(mut bytes: &[u8], signature: &[u8], hash_len: usize) -> bool {
+    // The trailing checksum is not an extension
+    while bytes.len() >= 8 + hash_len {
+        if &bytes[..4] == signature {
+            return true;
+        }
+        let size = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+        match bytes.get(8 + size..) {
+            Some(rest) => bytes = rest,
+            None => return false,
+        }
+    }
+    false
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos + len;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or("index file is truncated")?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn until_nul(&mut self) -> Result<&'a [u8], String> {
+        let rest = &self.bytes[self.pos..];
+        let len = rest
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or("unterminated path in index entry")?;
+        self.pos += len + 1;
+        Ok(&rest[..len])
+    }
+
+    /// git's offset encoding: big-endian 7-bit groups where each
+    /// continuation also adds one, so every value has a single encoding.
+    fn varint(&mut self) -> Result<usize, String> {
+        let mut byte = self.take(1)?[0];
+        let mut value = (byte & 0x7f) as usize;
+        while byte & 0x80 != 0 {
+            byte = self.take(1)?[0];
+            value = ((value + 1) << 7) | (byte & 0x7f) as usize;
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::tests::git;
+    use tempfile::tempdir;
+
+    fn repo() -> tempfile::TempDir {
+        let tmp = tempdir().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        for path in [
+            "README.md",
+            "src/lib.rs",
+            "src/nested/deep/mod.rs",
+            "docs/My Guide.md",
+        ] {
+            let path = tmp.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "content").unwrap();
+        }
+        git(tmp.path(), &["add", "."]);
+        tmp
+    }
+
+    fn paths(entries: &[IndexEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.path.as_str()).collect()
+    }
+
+    #[test]
+    fn reads_every_index_version() {
+        let tmp = repo();
+        let expected = [
+            "README.md",
+            "docs/My Guide.md",
+            "src/lib.rs",
+            "src/nested/deep/mod.rs",
+        ];
+
+        for version in ["2", "3", "4"] {
+            git(tmp.path(), &["update-index", "--index-version", version]);
+            let entries = read_index(tmp.path()).unwrap();
+            assert_eq!(paths(&entries), expected, "index version {}", version);
+        }
+    }
+
+    #[test]
+    fn reads_extended_flags() {
+        let tmp = repo();
+        fs::write(tmp.path().join("later.txt"), "").unwrap();
+        // Intent-to-add entries need the extended flags of version 3
+        git(tmp.path(), &["add", "--intent-to-add", "later.txt"]);
+        git(
+            tmp.path(),
+            &["update-index", "--skip-worktree", "README.md"],
+        );
+
+        let entries = read_index(tmp.path()).unwrap();
+        assert!(paths(&entries).contains(&"later.txt"));
+        assert!(paths(&entries).contains(&"README.md"));
+    }
+
+    #[test]
+    fn classifies_gitlinks_and_symlinks() {
+        let tmp = repo();
+        let commit = "0123456789012345678901234567890123456789";
+        git(
+            tmp.path(),
+            &[
+                "update-index",
+                "--add",
+                "--cacheinfo",
+                &format!("160000,{},vendor/lib", commit),
+            ],
+        );
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("README.md", tmp.path().join("link")).unwrap();
+            git(tmp.path(), &["add", "link"]);
+        }
+
+        let entries = read_index(tmp.path()).unwrap();
+        let kind = |path: &str| {
+            entries
+                .iter()
+                .find(|entry| entry.path == path)
+                .unwrap()
+                .kind
+        };
+        assert_eq!(kind("vendor/lib"), IndexEntryKind::Gitlink);
+        assert_eq!(kind("README.md"), IndexEntryKind::File);
+        #[cfg(unix)]
+        assert_eq!(kind("link"), IndexEntryKind::Symlink);
+    }
+
+    #[test]
+    fn empty_repository_has_no_entries() {
+        let tmp = tempdir().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        assert!(read_index(tmp.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(parse_index(b"PACK\0\0\0\x02", 20).is_err());
+        assert!(parse_index(b"DIRC\0\0\0\x02\0\0\0\x01", 20)
+            .unwrap_err()
+            .contains("truncated"));
+    }
+
+    #[test]
+    fn rejects_counts_the_file_cannot_hold() {
+        let mut bytes = b"DIRC\0\0\0\x02\x0f\xff\xff\xff".to_vec();
+        bytes.resize(32, 0);
+        assert!(parse_index(&bytes, 20).is_err());
+
+        let tmp = tempdir().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        fs::write(tmp.path().join(".git/index"), &bytes).unwrap();
+        assert!(matches!(read_index(tmp.path()), Err(Error::Git { .. })));
+    }
+
+    #[test]
+    fn requires_a_git_repository() {
+        let tmp = tempdir().unwrap();
+        assert!(matches!(read_index(tmp.path()), Err(Error::Git { .. })));
+    }
+}
@@ -0,0 +1,100 @@
+//! Just enough of git's on-disk formats to list tracked files and read
+//! revisions without running `git`.
+//!
+//! This is read-only and limited to what the checks need: the index,
+//! loose and packed objects, deltas and refs. A full implementation such
+//! as `gix` would bring in dozens of crates for writing, networking and
+//! config that the Action binary never uses, and would multiply its build
+//! time. Replace refs and grafts are not applied, and objects missing
+//! from a partial clone fail with `Error::Git`.
+
+pub mod index;
+pub mod object;
+mod pack;
+pub mod repository;
+pub mod submodule;
+
+use crate::error::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Object ids are SHA-1 unless the repository opted into SHA-256.
+const SHA1_LEN: usize = 20;
+const SHA256_LEN: usize = 32;
+
+/// Finds the git directory of the work tree at `repo_root`: either `.git`
+/// itself or, for linked worktrees and submodules, the directory a `.git`
+/// file points to.
+pub fn git_dir(repo_root: &Path) -> Result<PathBuf> {
+    let dot_git = repo_root.join(".git");
+    if dot_git.is_dir() {
+        return Ok(dot_git);
+    }
+
+    let contents = match fs::read_to_string(&dot_git) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(Error::git(
+                repo_root,
+                "not a git repository (use the worktree file source instead)",
+            ))
+        }
+        Err(err) => return Err(Error::io(dot_git, err)),
+    };
+    let target = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("gitdir:"))
+        .map(str::trim)
+        .ok_or_else(|| Error::git(&dot_git, "expected a `gitdir:` line"))?;
+
+    Ok(repo_root.join(target))
+}
+
+/// The directory holding objects and config, shared by all worktrees.
+pub(crate) fn common_dir(git_dir: &Path) -> PathBuf {
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common) => git_dir.join(common.trim()),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+/// Length in bytes of an object id in this repository.
+pub(crate) fn hash_len(git_dir: &Path) -> usize {
+    let config = fs::read_to_string(common_dir(git_dir).join("config")).unwrap_or_default();
+    let sha256 = config.lines().any(|line| {
+        let line = line.trim().to_ascii_lowercase();
+        line.starts_with("objectformat") && line.ends_with("sha256")
+    });
+    if sha256 {
+        SHA256_LEN
+    } else {
+        SHA1_LEN
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Runs `git` in `dir`, panicking if it fails. Commits and tags get a
+    /// fixed identity so they work without any git config.
+    pub fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .output()
+            .expect("git must be installed to run these tests");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    }
+}
@@ -0,0 +1,202 @@
+use flate2::read::ZlibDecoder;
+use std::fmt;
+use std::io::Read;
+
+/// The id of a git object: a SHA-1 or SHA-256 hash.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectId {
+    bytes: [u8; 32],
+    len: u8,
+}
+
+impl ObjectId {
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 20 && bytes.len() != 32 {
+            return None;
+        }
+        let mut id = ObjectId {
+            bytes: [0; 32],
+            len: bytes.len() as u8,
+        };
+        id.bytes[..bytes.len()].copy_from_slice(bytes);
+        Some(id)
+    }
+
+    /// Parses a full-length hex id.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.as_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl fmt::Debug for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ObjectId({})", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Commit,
+    Tree,
+    Blob,
+    Tag,
+}
+
+impl ObjectKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ObjectKind::Commit => "commit",
+            ObjectKind::Tree => "tree",
+            ObjectKind::Blob => "blob",
+            ObjectKind::Tag => "tag",
+        }
+    }
+
+    fn from_name(name: &[u8]) -> Option<Self> {
+        match name {
+            b"commit" => Some(ObjectKind::Commit),
+            b"tree" => Some(ObjectKind::Tree),
+            b"blob" => Some(ObjectKind::Blob),
+            b"tag" => Some(ObjectKind::Tag),
+            _ => None,
+        }
+    }
+
+    /// The type numbers used in pack files.
+    pub(crate) fn from_pack_type(pack_type: u8) -> Option<Self> {
+        match pack_type {
+            1 => Some(ObjectKind::Commit),
+            2 => Some(ObjectKind::Tree),
+            3 => Some(ObjectKind::Blob),
+            4 => Some(ObjectKind::Tag),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    pub kind: ObjectKind,
+    pub data: Vec<u8>,
+}
+
+impl Object {
+    /// The id in a `<name> <hex>` header line of a commit or tag, e.g.
+    /// `tree` in a commit or `object` in a tag.
+    pub fn header(&self, name: &str) -> Option<ObjectId> {
+        self.headers(name).next()
+    }
+
+    /// Every id in a `<name> <hex>` header line, e.g. the `parent` lines of
+    /// a merge commit.
+    pub fn headers<'a>(&'a self, name: &'a str) -> impl Iterator<Item = ObjectId> + 'a {
+        self.data
+            .split(|&byte| byte == b'\n')
+            .take_while(|line| !line.is_empty())
+            .filter_map(move |line| {
+                let value = line.strip_prefix(name.as_bytes())?.strip_prefix(b" ")?;
+                ObjectId::from_hex(std::str::from_utf8(value).ok()?)
+            })
+    }
+}
+
+/// Parses a loose object: a zlib stream of `<kind> <size>\0<data>`.
+pub(crate) fn parse_loose(compressed: &[u8]) -> Result<Object, String> {
+    let mut bytes = Vec::new();
+    ZlibDecoder::new(compressed)
+        .read_to_end(&mut bytes)
+        .map_err(|err| format!("corrupt loose object: {}", err))?;
+
+    let nul = bytes
+        .iter()
+        .position(|&byte| byte == 0)
+        .ok_or("loose object has no header")?;
+    let header = &bytes[..nul];
+    let (kind, size) = header
+        .iter()
+        .position(|&byte| byte == b' ')
+        .map(|space| (&header[..space], &header[space + 1..]))
+        .ok_or("malformed loose object header")?;
+    let kind = ObjectKind::from_name(kind).ok_or("unknown loose object type")?;
+    let size: usize = std::str::from_utf8(size)
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .ok_or("malformed loose object size")?;
+
+    let data = bytes.split_off(nul + 1);
+    if data.len() != size {
+        return Err("loose object size does not match its header".to_string());
+    }
+    Ok(Object { kind, data })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn round_trips_hex_ids() {
+        let hex = "0123456789abcdef0123456789abcdef01234567";
+        let id = ObjectId::from_hex(hex).unwrap();
+        assert_eq!(id.as_bytes().len(), 20);
+        assert_eq!(id.to_string(), hex);
+        assert_eq!(ObjectId::from_hex("0123"), None);
+        assert_eq!(ObjectId::from_hex(&hex.replace('0', "g")), None);
+    }
+
+    #[test]
+    fn parses_loose_objects() {
+        let object = parse_loose(&compress(b"blob 5\0hello")).unwrap();
+        assert_eq!(object.kind, ObjectKind::Blob);
+        assert_eq!(object.data, b"hello");
+
+        assert!(parse_loose(&compress(b"blob 9\0hello")).is_err());
+        assert!(parse_loose(&compress(b"note 5\0hello")).is_err());
+        assert!(parse_loose(b"not zlib").is_err());
+    }
+
+    #[test]
+    fn reads_header_ids() {
+        let tree = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+        let commit = Object {
+            kind: ObjectKind::Commit,
+            data: format!("tree {}\nauthor a <a@b> 0 +0000\n\ntree in message\n", tree)
+                .into_bytes(),
+        };
+        assert_eq!(
+            commit.header("tree").map(|id| id.to_string()),
+            Some(tree.to_string())
+        );
+        assert_eq!(commit.header("parent"), None);
+    }
+}
@@ -0,0 +1,364 @@
+use crate::error::{Error, Result};
+use crate::git::object::{Object, ObjectId, ObjectKind};
+use flate2::bufread::ZlibDecoder;
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+const INDEX_SIGNATURE: &[u8] = b"\xfftOc";
+const FANOUT_LEN: usize = 256 * 4;
+/// Offsets with this bit set point into the table of 64-bit offsets
+const LARGE_OFFSET: u32 = 0x8000_0000;
+
+// Pack entry types that are deltas against another object
+const OFS_DELTA: u8 = 6;
+const REF_DELTA: u8 = 7;
+
+/// git stops deltifying long before this; a longer chain means a cycle
+const MAX_DELTA_DEPTH: usize = 10_000;
+
+/// Most bytes reserved up front for an object whose size a pack header
+/// claims. Bigger objects grow the buffer as they are read, so a corrupt
+/// header fails the read instead of the allocation.
+const MAX_PREALLOCATION: usize = 16 << 20;
+
+/// A pack file and its version 2 index.
+pub(crate) struct Pack {
+    path: PathBuf,
+    index: Vec<u8>,
+    file: File,
+    hash_len: usize,
+    count: usize,
+}
+
+impl Pack {
+    /// Opens the pack belonging to the `.idx` file at `index_path`.
+    pub fn open(index_path: &Path, hash_len: usize) -> Result<Self> {
+        let index = fs::read(index_path).map_err(|err| Error::io(index_path, err))?;
+        let path = index_path.with_extension("pack");
+        let file = File::open(&path).map_err(|err| Error::io(&path, err))?;
+
+        if !index.starts_with(INDEX_SIGNATURE) || index.get(4..8) != Some(&[0, 0, 0, 2]) {
+            return Err(Error::git(
+                index_path,
+                "only version 2 pack indexes are supported",
+            ));
+        }
+        let count = read_u32(&index, 8 + FANOUT_LEN - 4).unwrap_or(0) as usize;
+        // Ids, CRCs and offsets, then the pack and index checksums
+        if index.len() < 8 + FANOUT_LEN + count * (hash_len + 8) + 2 * hash_len {
+            return Err(Error::git(index_path, "pack index is truncated"));
+        }
+
+        Ok(Pack {
+            path,
+            index,
+            file,
+            hash_len,
+            count,
+        })
+    }
+
+    fn id_at(&self, position: usize) -> &[u8] {
+        let start = 8 + FANOUT_LEN + position * self.hash_len;
+        &self.index[start..start + self.hash_len]
+    }
+
+    /// The positions of the ids starting with `first_byte`.
+    fn bucket(&self, first_byte: u8) -> std::ops::Range<usize> {
+        let fanout = |byte: usize| read_u32(&self.index, 8 + byte * 4).unwrap_or(0) as usize;
+        let end = fanout(first_byte as usize).min(self.count);
+        let start = match first_byte {
+            0 => 0,
+            byte => fanout(byte as usize - 1).min(end),
+        };
+        start..end
+    }
+
+    fn position(&self, id: &ObjectId) -> Option<usize> {
+        let bytes = id.as_bytes();
+        let mut range = self.bucket(bytes[0]);
+        while !range.is_empty() {
+            let mid = range.start + range.len() / 2;
+            match self.id_at(mid).cmp(bytes) {
+                std::cmp::Ordering::Equal => return Some(mid),
+                std::cmp::Ordering::Less => range.start = mid + 1,
+                std::cmp::Ordering::Greater => range.end = mid,
+            }
+        }
+        None
+    }
+
+    /// The ids in this pack whose hex form starts with `prefix`, which
+    /// must be at least two characters long.
+    pub fn ids_with_prefix(&self, prefix: &str) -> Vec<ObjectId> {
+        let Some(first_byte) = prefix
+            .get(..2)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        else {
+            return Vec::new();
+        };
+        self.bucket(first_byte)
+            .filter_map(|position| ObjectId::from_bytes(self.id_at(position)))
+            .filter(|id| id.to_hex().starts_with(prefix))
+            .collect()
+    }
+
+    fn offset(&self, position: usize) -> Result<u64> {
+        let offsets = 8 + FANOUT_LEN + self.count * (self.hash_len + 4);
+        let offset = read_u32(&self.index, offsets + position * 4).unwrap_or(0);
+        if offset & LARGE_OFFSET == 0 {
+            return Ok(offset as u64);
+        }
+        let large = offsets + self.count * 4 + (offset & !LARGE_OFFSET) as usize * 8;
+        self.index
+            .get(large..large + 8)
+            .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+            .ok_or_else(|| self.error("pack index is truncated"))
+    }
+
+    /// Reads an object from this pack. `read_base` looks up the bases of
+    /// deltas that refer to their base by id, which may live elsewhere.
+    pub fn read(
+        &self,
+        id: &ObjectId,
+        read_base: &dyn Fn(&ObjectId) -> Result<Object>,
+    ) -> Result<Option<Object>> {
+        let Some(position) = self.position(id) else {
+            return Ok(None);
+        };
+        let mut offset = self.offset(position)?;
+
+        // Follow the delta chain down to a full object, then apply the
+        // deltas from the bottom up
+        let mut deltas = Vec::new();
+        let mut base = loop {
+            if deltas.len() > MAX_DELTA_DEPTH {
+                return Err(self.error("delta chain is too long"));
+            }
+            let entry = self.entry_at(offset)?;
+            match entry.base {
+                Base::None(kind) => {
+                    break Object {
+                        kind,
+                        data: entry.data,
+                    }
+                }
+                Base::Offset(base_offset) => {
+                    deltas.push(entry.data);
+                    offset = base_offset;
+                }
+                Base::Id(base_id) => {
+                    deltas.push(entry.data);
+                    match self.position(&base_id) {
+                        Some(position) => offset = self.offset(position)?,
+                        None => break read_base(&base_id)?,
+                    }
+                }
+            }
+        };
+
+        while let Some(delta) = deltas.pop() {
+            base.data = apply_delta(&base.data, &delta).map_err(|message| self.error(message))?;
+        }
+        Ok(Some(base))
+    }
+
+    fn entry_at(&self, offset: u64) -> Result<Entry> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|err| Error::io(&self.path, err))?;
+        let mut reader = BufReader::new(file);
+        let mut next_byte = || -> Result<u8> {
+            let mut byte = [0];
+            reader
+                .read_exact(&mut byte)
+                .map_err(|err| Error::io(&self.path, err))?;
+            Ok(byte[0])
+        };
+
+        // Type in bits 4-6 of the first byte, then the size in 7-bit
+        // little-endian groups
+        let mut byte = next_byte()?;
+        let pack_type = (byte >> 4) & 0x7;
+        let mut size = (byte & 0x0f) as usize;
+        let mut shift = 4;
+        while byte & 0x80 != 0 {
+            byte = next_byte()?;
+            size |= ((byte & 0x7f) as usize)
+                .checked_shl(shift)
+                .ok_or_else(|| self.error("object size overflows"))?;
+            shift += 7;
+        }
+
+        let base = match pack_type {
+            OFS_DELTA => {
+                // Big-endian groups where each continuation also adds one
+                let mut byte = next_byte()?;
+                let mut distance = (byte & 0x7f) as u64;
+                while byte & 0x80 != 0 {
+                    byte = next_byte()?;
+                    distance = ((distance + 1) << 7) | (byte & 0x7f) as u64;
+                }
+                let base_offset = offset
+                    .checked_sub(distance)
+                    .filter(|_| distance != 0)
+                    .ok_or_else(|| self.error("delta base offset is out of range"))?;
+                Base::Offset(base_offset)
+            }
+            REF_DELTA => {
+                let mut bytes = vec![0; self.hash_len];
+                reader
+                    .read_exact(&mut bytes)
+                    .map_err(|err| Error::io(&self.path, err))?;
+                Base::Id(ObjectId::from_bytes(&bytes).ok_or_else(|| self.error("bad base id"))?)
+            }
+            other => Base::None(
+                ObjectKind::from_pack_type(other)
+                    .ok_or_else(|| self.error(format!("unknown object type {}", other)))?,
+            ),
+        };
+
+        let mut data = Vec::with_capacity(size.min(MAX_PREALLOCATION));
+        ZlibDecoder::new(reader)
+            .take(size as u64)
+            .read_to_end(&mut data)
+            .map_err(|err| self.error(format!("corrupt object at offset {}: {}", offset, err)))?;
+        if data.len() != size {
+            return Err(self.error(format!("object at offset {} is truncated", offset)));
+        }
+        Ok(Entry { base, data })
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::git(&self.path, message)
+    }
+}
+
+enum Base {
+    /// A full object
+    None(ObjectKind),
+    Offset(u64),
+    Id(ObjectId),
+}
+
+/// A pack entry: a full object, or a delta and where its base is.
+struct Entry {
+    base: Base,
+    data: Vec<u8>,
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    bytes
+        .get(at..at + 4)
+        .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+/// Applies a git delta: the base and result sizes, then instructions that
+/// either copy a range of the base or insert literal bytes.
+pub(crate) fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>, String> {
+    let mut pos = 0;
+    let mut size = || -> Result<usize, String> {
+        let mut value = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = *delta.get(pos).ok_or("delta is truncated")?;
+            pos += 1;
+            value |= ((byte & 0x7f) as usize)
+                .checked_shl(shift)
+                .ok_or("delta size overflows")?;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    };
+    if size()? != base.len() {
+        return Err("delta does not apply to its base".to_string());
+    }
+    let result_len = size()?;
+
+    // The result is built from the base and the delta, so it is never
+    // worth reserving more than both together
+    let mut result = Vec::with_capacity(result_len.min(base.len().saturating_add(delta.len())));
+    while let Some(&op) = delta.get(pos) {
+        pos += 1;
+        if op & 0x80 != 0 {
+            // Each set bit says which offset and size bytes follow
+            let mut field = |bits: std::ops::Range<u8>| -> Result<usize, String> {
+                let mut value = 0usize;
+                for (i, bit) in bits.enumerate() {
+                    if op & (1 << bit) != 0 {
+                        let byte = *delta.get(pos).ok_or("delta is truncated")?;
+                        pos += 1;
+                        value |= (byte as usize) << (8 * i);
+                    }
+                }
+                Ok(value)
+            };
+            let offset = field(0..4)?;
+            let len = match field(4..7)? {
+                0 => 0x10000,
+                len => len,
+            };
+            let copy = offset
+                .checked_add(len)
+                .and_then(|end| base.get(offset..end))
+                .ok_or("delta copies past the end of its base")?;
+            result.extend_from_slice(copy);
+        } else if op != 0 {
+            let insert = delta
+                .get(pos..pos + op as usize)
+                .ok_or("delta is truncated")?;
+            result.extend_from_slice(insert);
+            pos += op as usize;
+        } else {
+            return Err("reserved delta instruction".to_string());
+        }
+        if result.len() > result_len {
+            return Err("delta result has the wrong size".to_string());
+        }
+    }
+
+    if result.len() != result_len {
+        return Err("delta result has the wrong size".to_string());
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_copy_and_insert_instructions() {
+        // Sizes 11 and 17, copy "hello " from offset 0, insert "there ",
+        // then copy "world" from offset 6
+        let delta = [
+            11, 17, 0x90, 6, 6, b't', b'h', b'e', b'r', b'e', b' ', 0x91, 6, 5,
+        ];
+        assert_eq!(
+            apply_delta(b"hello world", &delta).unwrap(),
+            b"hello there world".to_vec()
+        );
+    }
+
+    #[test]
+    fn rejects_deltas_for_other_bases() {
+        assert!(apply_delta(b"abc", &[4, 1, 1, b'x']).is_err());
+        assert!(apply_delta(b"abc", &[3, 1, 0x91, 9, 1]).is_err());
+        assert!(apply_delta(b"abc", &[3, 2, 1, b'x']).is_err());
+    }
+
+    #[test]
+    fn rejects_deltas_claiming_huge_results() {
+        // A result size of 2^62 bytes must not be allocated up front
+        let mut delta = vec![3];
+        delta.extend([0x80; 8]);
+        delta.extend([0x40, 0x91, 0, 3]);
+        assert_eq!(
+            apply_delta(b"abc", &delta),
+            Err("delta result has the wrong size".to_string())
+        );
+    }
+}
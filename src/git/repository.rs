@@ -0,0 +1,634 @@
+use crate::error::{Error, Result};
+use crate::git::object::{parse_loose, Object, ObjectId, ObjectKind};
+use crate::git::pack::Pack;
+use crate::git::{common_dir, git_dir, hash_len};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Where `resolve` looks for a name, in git's order: `main` can be a
+/// branch, `v1.0` a tag and `origin/main` a remote-tracking branch.
+const REF_RULES: &[&str] = &[
+    "{}",
+    "refs/{}",
+    "refs/tags/{}",
+    "refs/heads/{}",
+    "refs/remotes/{}",
+    "refs/remotes/{}/HEAD",
+];
+
+/// Symbolic refs pointing at symbolic refs are rare; more than this many
+/// means a loop.
+const MAX_SYMREF_DEPTH: usize = 5;
+
+/// Abbreviated ids shorter than this are rejected, like git does.
+const MIN_ABBREV_LEN: usize = 4;
+
+/// What a tree entry records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeEntryKind {
+    File,
+    Symlink,
+    Directory,
+    /// A submodule commit
+    Gitlink,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// Slash-separated path relative to the root tree. Bytes that are not
+    /// valid UTF-8 are replaced.
+    pub path: String,
+    pub kind: TreeEntryKind,
+    pub id: ObjectId,
+}
+
+/// Read access to a repository's refs and object database, for work
+/// trees and bare repositories alike.
+pub struct Repository {
+    git_dir: PathBuf,
+    common_dir: PathBuf,
+    /// The object directory and any alternates it borrows objects from
+    object_dirs: Vec<PathBuf>,
+    packs: Vec<Pack>,
+    hash_len: usize,
+}
+
+impl Repository {
+    /// Opens the repository at `path`: a work tree, or a bare repository.
+    pub fn open(path: &Path) -> Result<Self> {
+        let git_dir = if path.join(".git").exists() {
+            git_dir(path)?
+        } else if is_bare(path) {
+            path.to_path_buf()
+        } else {
+            return Err(Error::git(path, "not a git repository"));
+        };
+        let common_dir = common_dir(&git_dir);
+        let hash_len = hash_len(&git_dir);
+
+        let mut object_dirs = vec![common_dir.join("objects")];
+        let alternates = common_dir.join("objects/info/alternates");
+        if let Ok(contents) = fs::read_to_string(&alternates) {
+            object_dirs.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| common_dir.join("objects").join(line)),
+            );
+        }
+
+        let mut packs = Vec::new();
+        for objects in &object_dirs {
+            let pack_dir = objects.join("pack");
+            let entries = match fs::read_dir(&pack_dir) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(Error::io(pack_dir, err)),
+            };
+            for entry in entries {
+                let path = entry.map_err(|err| Error::io(&pack_dir, err))?.path();
+                if path.extension().is_some_and(|ext| ext == "idx") {
+                    packs.push(Pack::open(&path, hash_len)?);
+                }
+            }
+        }
+
+        Ok(Repository {
+            git_dir,
+            common_dir,
+            object_dirs,
+            packs,
+            hash_len,
+        })
+    }
+
+    pub fn read(&self, id: &ObjectId) -> Result<Object> {
+        let hex = id.to_hex();
+        for objects in &self.object_dirs {
+            let path = objects.join(&hex[..2]).join(&hex[2..]);
+            match fs::read(&path) {
+                Ok(compressed) => {
+                    return parse_loose(&compressed).map_err(|message| Error::git(path, message))
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(Error::io(path, err)),
+            }
+        }
+
+        for pack in &self.packs {
+            if let Some(object) = pack.read(id, &|base| self.read(base))? {
+                return Ok(object);
+            }
+        }
+        Err(Error::git(
+            &self.git_dir,
+            format!("object {} is missing", id),
+        ))
+    }
+
+    /// Resolves a revision to an object id: a full or abbreviated object
+    /// id, or a branch, tag, remote-tracking branch or other ref name,
+    /// optionally followed by `~<n>` and `^<n>` to walk to an ancestor.
+    pub fn resolve(&self, rev: &str) -> Result<ObjectId> {
+        let (name, mut suffix) = rev.split_at(rev.find(['~', '^']).unwrap_or(rev.len()));
+        let mut id = self.resolve_name(name)?;
+
+        while let Some(op) = suffix.chars().next() {
+            if op != '~' && op != '^' {
+                return Err(Error::Config(format!(
+                    "unsupported revision syntax `{}`",
+                    rev
+                )));
+            }
+            // Both operators are one byte, so slicing past them is safe
+            let digits = suffix[1..].bytes().take_while(u8::is_ascii_digit).count();
+            let count: usize = match &suffix[1..1 + digits] {
+                "" => 1,
+                count => count.parse().map_err(|_| {
+                    Error::Config(format!("ancestor count in `{}` is too large", rev))
+                })?,
+            };
+            suffix = &suffix[1 + digits..];
+            id = match op {
+                '~' => (0..count).try_fold(id, |id, _| self.parent(&id, 1, rev))?,
+                _ => self.parent(&id, count, rev)?,
+            };
+        }
+        Ok(id)
+    }
+
+    /// The `n`th parent of a commit, counting from 1, or the commit itself
+    /// for 0. Tags are peeled to the commit they point to.
+    fn parent(&self, id: &ObjectId, n: usize, rev: &str) -> Result<ObjectId> {
+        let mut id = *id;
+        let commit = loop {
+            let object = self.read(&id)?;
+            match object.kind {
+                ObjectKind::Commit => break object,
+                ObjectKind::Tag => {
+                    id = object.header("object").ok_or_else(|| {
+                        Error::git(&self.git_dir, format!("tag {} has no `object` line", id))
+                    })?;
+                }
+                kind => {
+                    return Err(Error::Config(format!(
+                        "`{}` walks the ancestors of a {}, not a commit",
+                        rev,
+                        kind.name()
+                    )))
+                }
+            }
+        };
+        match n {
+            0 => Ok(id),
+            n => commit.headers("parent").nth(n - 1).ok_or_else(|| {
+                Error::Config(format!(
+                    "unknown revision `{}`: commit {} has no parent {}",
+                    rev, id, n
+                ))
+            }),
+        }
+    }
+
+    fn resolve_name(&self, rev: &str) -> Result<ObjectId> {
+        if rev.len() == self.hash_len * 2 {
+            if let Some(id) = ObjectId::from_hex(rev) {
+                return Ok(id);
+            }
+        }
+
+        for rule in REF_RULES {
+            if let Some(id) = self.read_ref(&rule.replace("{}", rev), 0)? {
+                return Ok(id);
+            }
+        }
+
+        let is_hex = rev.bytes().all(|byte| byte.is_ascii_hexdigit());
+        if is_hex && rev.len() >= MIN_ABBREV_LEN {
+            let prefix = rev.to_ascii_lowercase();
+            let mut ids = self.ids_with_prefix(&prefix)?;
+            ids.sort_by_key(ObjectId::to_hex);
+            ids.dedup();
+            match ids.as_slice() {
+                [id] => return Ok(*id),
+                [] => {}
+                _ => {
+                    return Err(Error::Config(format!(
+                        "revision `{}` is ambiguous: {} objects start with it",
+                        rev,
+                        ids.len()
+                    )))
+                }
+            }
+        }
+
+        Err(Error::Config(format!("unknown revision `{}`", rev)))
+    }
+
+    /// Resolves a revision and peels tags and commits down to their tree.
+    pub fn resolve_tree(&self, rev: &str) -> Result<ObjectId> {
+        let mut id = self.resolve(rev)?;
+        loop {
+            let object = self.read(&id)?;
+            let header = match object.kind {
+                ObjectKind::Tree => return Ok(id),
+                ObjectKind::Commit => "tree",
+                ObjectKind::Tag => "object",
+                ObjectKind::Blob => {
+                    return Err(Error::Config(format!(
+                        "revision `{}` is a blob, not a commit or tree",
+                        rev
+                    )))
+                }
+            };
+            id = object.header(header).ok_or_else(|| {
+                Error::git(
+                    &self.git_dir,
+                    format!("{} {} has no `{}` line", object.kind.name(), id, header),
+                )
+            })?;
+        }
+    }
+
+    /// Lists every entry under `tree`, recursing into subtrees but not
+    /// into submodules. Directories are listed before their contents.
+    pub fn list_tree(&self, tree: &ObjectId) -> Result<Vec<TreeEntry>> {
+        let mut entries = Vec::new();
+        let mut pending = vec![(String::new(), *tree)];
+        while let Some((prefix, id)) = pending.pop() {
+            for entry in self.read_tree(&id, &prefix)? {
+                if entry.kind == TreeEntryKind::Directory {
+                    pending.push((format!("{}/", entry.path), entry.id));
+                }
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Looks up a path, relative to `tree`, without listing the whole tree.
+    pub fn find_entry(&self, tree: &ObjectId, path: &Path) -> Result<Option<TreeEntry>> {
+        let mut found: Option<TreeEntry> = None;
+        let mut tree = *tree;
+        for segment in path.iter() {
+            if let Some(entry) = &found {
+                if entry.kind != TreeEntryKind::Directory {
+                    return Ok(None);
+                }
+                tree = entry.id;
+            }
+            let segment = segment.to_string_lossy();
+            let prefix = found
+                .as_ref()
+                .map(|entry| format!("{}/", entry.path))
+                .unwrap_or_default();
+            let entries = self.read_tree(&tree, &prefix)?;
+            match entries
+                .into_iter()
+                .find(|entry| entry.path[prefix.len()..] == *segment)
+            {
+                Some(entry) => found = Some(entry),
+                None => return Ok(None),
+            }
+        }
+        Ok(found)
+    }
+
+    /// Reads the file at `path` in `tree`, if there is one.
+    pub fn read_file(&self, tree: &ObjectId, path: &Path) -> Result<Option<Vec<u8>>> {
+        match self.find_entry(tree, path)? {
+            Some(entry) if entry.kind == TreeEntryKind::File => {
+                Ok(Some(self.read(&entry.id)?.data))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn read_tree(&self, id: &ObjectId, prefix: &str) -> Result<Vec<TreeEntry>> {
+        let object = self.read(id)?;
+        if object.kind != ObjectKind::Tree {
+            return Err(Error::git(
+                &self.git_dir,
+                format!(
+                    "expected {} to be a tree, found a {}",
+                    id,
+                    object.kind.name()
+                ),
+            ));
+        }
+        parse_tree(&object.data, prefix, self.hash_len)
+            .map_err(|message| Error::git(&self.git_dir, format!("tree {}: {}", id, message)))
+    }
+
+    /// Reads a ref, following symbolic refs. Per-worktree refs like `HEAD`
+    /// live in the git directory, shared ones in the common directory.
+    fn read_ref(&self, name: &str, depth: usize) -> Result<Option<ObjectId>> {
+        if depth > MAX_SYMREF_DEPTH || !is_valid_ref_name(name) {
+            return Ok(None);
+        }
+
+        for dir in [&self.git_dir, &self.common_dir] {
+            let path = dir.join(name);
+            let contents = match fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                // A directory such as `refs/heads` is not a ref
+                Err(err) if matches!(err.kind(), ErrorKind::NotFound | ErrorKind::IsADirectory) => {
+                    continue
+                }
+                Err(err) => return Err(Error::io(path, err)),
+            };
+            let contents = contents.trim();
+            if let Some(target) = contents.strip_prefix("ref:") {
+                return self.read_ref(target.trim(), depth + 1);
+            }
+            return ObjectId::from_hex(contents)
+                .map(Some)
+                .ok_or_else(|| Error::git(path, "malformed ref"));
+        }
+
+        let packed = self.common_dir.join("packed-refs");
+        let contents = match fs::read_to_string(&packed) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::io(packed, err)),
+        };
+        // `#` starts the header and `^` the peeled id of the tag above
+        Ok(contents
+            .lines()
+            .filter(|line| !line.starts_with(['#', '^']))
+            .filter_map(|line| line.split_once(' '))
+            .find(|(_, ref_name)| *ref_name == name)
+            .and_then(|(hex, _)| ObjectId::from_hex(hex)))
+    }
+
+    fn ids_with_prefix(&self, prefix: &str) -> Result<Vec<ObjectId>> {
+        let mut ids = Vec::new();
+        for objects in &self.object_dirs {
+            let dir = objects.join(&prefix[..2]);
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(Error::io(dir, err)),
+            };
+            for entry in entries {
+                let name = entry.map_err(|err| Error::io(&dir, err))?.file_name();
+                let hex = format!("{}{}", &prefix[..2], name.to_string_lossy());
+                if hex.starts_with(prefix) {
+                    ids.extend(ObjectId::from_hex(&hex));
+                }
+            }
+        }
+        for pack in &self.packs {
+            ids.extend(pack.ids_with_prefix(prefix));
+        }
+        Ok(ids)
+    }
+}
+
+/// A bare repository has its git data at the top level instead of in `.git`.
+fn is_bare(path: &Path) -> bool {
+    path.join("HEAD").is_file() && path.join("objects").is_dir()
+}
+
+/// Rejects names that would read other files in the git directory, like
+/// `config` or `../config`. Only refs under `refs/` and all-caps names like
+/// `HEAD` or `FETCH_HEAD` are refs.
+fn is_valid_ref_name(name: &str) -> bool {
+    let is_pseudo_ref = !name.is_empty()
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_uppercase() || byte == b'_');
+    (name.starts_with("refs/") || is_pseudo_ref)
+        && !name.ends_with('/')
+        && name
+            .split('/')
+            .all(|segment| !segment.is_empty() && !segment.starts_with('.'))
+}
+
+/// Parses tree entries: `<mode> <name>\0<id>`, one after another.
+fn parse_tree(data: &[u8], prefix: &str, hash_len: usize) -> Result<Vec<TreeEntry>, String> {
+    let mut entries = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let space = rest
+            .iter()
+            .position(|&byte| byte == b' ')
+            .ok_or("malformed tree entry")?;
+        let nul = rest
+            .iter()
+            .position(|&byte| byte == 0)
+            .filter(|&nul| nul > space)
+            .ok_or("malformed tree entry")?;
+        let id = rest
+            .get(nul + 1..nul + 1 + hash_len)
+            .and_then(ObjectId::from_bytes)
+            .ok_or("tree is truncated")?;
+
+        let kind = match &rest[..space] {
+            b"40000" => TreeEntryKind::Directory,
+            b"160000" => TreeEntryKind::Gitlink,
+            b"120000" => TreeEntryKind::Symlink,
+            _ => TreeEntryKind::File,
+        };
+        entries.push(TreeEntry {
+            path: format!(
+                "{}{}",
+                prefix,
+                String::from_utf8_lossy(&rest[space + 1..nul])
+            ),
+            kind,
+            id,
+        });
+        rest = &rest[nul + 1 + hash_len..];
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::tests::git;
+    use tempfile::tempdir;
+
+    fn commit_all(dir: &Path, message: &str) {
+        git(dir, &["add", "-A"]);
+        git(dir, &["commit", "-q", "-m", message]);
+    }
+
+    fn write(dir: &Path, path: &str, contents: &str) {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn file_at(repo: &Repository, rev: &str, path: &str) -> Option<String> {
+        let tree = repo.resolve_tree(rev).unwrap();
+        repo.read_file(&tree, Path::new(path))
+            .unwrap()
+            .map(|bytes| String::from_utf8(bytes).unwrap())
+    }
+
+    /// Two commits of a file large enough for git to store the second as
+    /// a delta once packed.
+    fn history() -> tempfile::TempDir {
+        let tmp = tempdir().unwrap();
+        git(tmp.path(), &["init", "-q", "-b", "main"]);
+        let text: String = (0..200).map(|i| format!("line {}\n", i)).collect();
+        write(tmp.path(), "docs/guide.md", &text);
+        write(tmp.path(), ".github/CODEOWNERS", "* @old\n");
+        commit_all(tmp.path(), "first");
+        git(tmp.path(), &["tag", "-a", "-m", "release", "v1"]);
+        write(tmp.path(), "docs/guide.md", &format!("{}more\n", text));
+        write(tmp.path(), ".github/CODEOWNERS", "* @new\n");
+        write(tmp.path(), "src/lib.rs", "");
+        commit_all(tmp.path(), "second");
+        tmp
+    }
+
+    #[test]
+    fn reads_loose_objects_at_any_revision() {
+        let tmp = history();
+        let repo = Repository::open(tmp.path()).unwrap();
+
+        assert_eq!(
+            file_at(&repo, "v1", ".github/CODEOWNERS").as_deref(),
+            Some("* @old\n")
+        );
+        assert_eq!(
+            file_at(&repo, "main", ".github/CODEOWNERS").as_deref(),
+            Some("* @new\n")
+        );
+        assert_eq!(file_at(&repo, "HEAD", "docs"), None);
+        assert_eq!(file_at(&repo, "v1", "src/lib.rs"), None);
+
+        let tree = repo.resolve_tree("main").unwrap();
+        let paths: Vec<(String, TreeEntryKind)> = repo
+            .list_tree(&tree)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.path, entry.kind))
+            .collect();
+        for expected in [
+            ("docs".to_string(), TreeEntryKind::Directory),
+            ("docs/guide.md".to_string(), TreeEntryKind::File),
+            ("src/lib.rs".to_string(), TreeEntryKind::File),
+        ] {
+            assert!(paths.contains(&expected), "{:?}", expected);
+        }
+    }
+
+    #[test]
+    fn reads_packed_objects_and_deltas() {
+        let tmp = history();
+        git(tmp.path(), &["pack-refs", "--all"]);
+        // Offset deltas first, then a pack whose deltas name their base
+        git(tmp.path(), &["repack", "-a", "-d", "-f", "-q"]);
+        let expected = git(tmp.path(), &["show", "v1:docs/guide.md"]);
+        {
+            let repo = Repository::open(tmp.path()).unwrap();
+            assert_eq!(
+                file_at(&repo, "v1", "docs/guide.md").as_deref(),
+                Some(expected.as_str())
+            );
+        }
+
+        git(
+            tmp.path(),
+            &[
+                "-c",
+                "repack.useDeltaBaseOffset=false",
+                "repack",
+                "-a",
+                "-d",
+                "-f",
+                "-q",
+            ],
+        );
+        let repo = Repository::open(tmp.path()).unwrap();
+        assert!(fs::read_dir(tmp.path().join(".git/refs/tags"))
+            .unwrap()
+            .next()
+            .is_none());
+        assert_eq!(
+            file_at(&repo, "v1", "docs/guide.md").as_deref(),
+            Some(expected.as_str())
+        );
+        assert_eq!(
+            file_at(&repo, "main", ".github/CODEOWNERS").as_deref(),
+            Some("* @new\n")
+        );
+    }
+
+    #[test]
+    fn resolves_ids_and_names() {
+        let tmp = history();
+        let repo = Repository::open(tmp.path()).unwrap();
+        let head = git(tmp.path(), &["rev-parse", "HEAD"]);
+        let head = head.trim();
+
+        assert_eq!(repo.resolve(head).unwrap().to_string(), head);
+        assert_eq!(repo.resolve(&head[..8]).unwrap().to_string(), head);
+        assert_eq!(repo.resolve("refs/heads/main").unwrap().to_string(), head);
+        assert_eq!(
+            repo.resolve("main~1").unwrap(),
+            repo.resolve("v1^0").unwrap()
+        );
+        assert_eq!(
+            repo.resolve("HEAD^^0").unwrap(),
+            repo.resolve("HEAD~").unwrap()
+        );
+        assert_eq!(
+            repo.resolve_tree("HEAD").unwrap().to_string(),
+            git(tmp.path(), &["rev-parse", "HEAD^{tree}"]).trim()
+        );
+
+        for rev in [
+            "missing",
+            "config",
+            "../config",
+            "refs/heads",
+            "ab",
+            "HEAD~2",
+            "HEAD^2",
+            "HEAD^{tree}",
+            // Multi-byte characters after an operator
+            "HEAD~é",
+            "HEAD^1é",
+        ] {
+            assert!(
+                matches!(repo.resolve(rev), Err(Error::Config(_))),
+                "{}",
+                rev
+            );
+        }
+        let blob = git(tmp.path(), &["rev-parse", "HEAD:src/lib.rs"]);
+        assert!(matches!(
+            repo.resolve_tree(blob.trim()),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn opens_bare_clones() {
+        let tmp = history();
+        let bare = tempdir().unwrap();
+        git(
+            bare.path(),
+            &[
+                "clone",
+                "-q",
+                "--bare",
+                tmp.path().to_str().unwrap(),
+                "repo.git",
+            ],
+        );
+
+        let repo = Repository::open(&bare.path().join("repo.git")).unwrap();
+        assert_eq!(
+            file_at(&repo, "v1", ".github/CODEOWNERS").as_deref(),
+            Some("* @old\n")
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::error::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A submodule declared in `.gitmodules`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submodule {
+    pub name: String,
+    /// Where the submodule is checked out, relative to the repository root
+    pub path: PathBuf,
+}
+
+/// Reads `.gitmodules` from the work tree at `repo_root`. A repository
+/// without one has no submodules.
+pub fn read_gitmodules(repo_root: &Path) -> Result<Vec<Submodule>> {
+    let path = repo_root.join(".gitmodules");
+    match fs::read(&path) {
+        Ok(bytes) => Ok(parse_gitmodules(&String::from_utf8_lossy(&bytes))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(Error::io(path, err)),
+    }
+}
+
+/// Parses the `[submodule "<name>"]` sections of a `.gitmodules` file.
+/// Sections without a `path` are skipped, as git skips them.
+pub fn parse_gitmodules(text: &str) -> Vec<Submodule> {
+    let mut submodules = Vec::new();
+    let mut section: Option<String> = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.split(']').next().unwrap_or_default();
+            section = header
+                .trim()
+                .strip_prefix("submodule")
+                .map(str::trim)
+                .and_then(|name| name.strip_prefix('"')?.strip_suffix('"'))
+                .map(str::to_string);
+            continue;
+        }
+        let Some(name) = &section else {
+            continue;
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if !key.trim().eq_ignore_ascii_case("path") {
+            continue;
+        }
+        let value = strip_comment(value.trim());
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        let value = value.trim_end_matches('/');
+        if value.is_empty() {
+            continue;
+        }
+
+        // A later `path` in the same section wins, as in git config
+        submodules.retain(|submodule: &Submodule| &submodule.name != name);
+        submodules.push(Submodule {
+            name: name.clone(),
+            path: PathBuf::from(value),
+        });
+    }
+
+    submodules
+}
+
+/// Drops a trailing `#` or `;` comment that is not inside quotes.
+fn strip_comment(value: &str) -> &str {
+    let mut quoted = false;
+    for (idx, ch) in value.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            '#' | ';' if !quoted => return value[..idx].trim_end(),
+            _ => {}
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_submodule_paths() {
+        let submodules = parse_gitmodules(
+            "# vendored code\n\
+             [submodule \"third_party/foo\"]\n\
+             \tpath = third_party/foo\n\
+             \turl = https://example.com/foo.git\n\
+             [core]\n\
+             \tpath = ignored\n\
+             [submodule \"bar\"]\n\
+             \tPath = \"libs/bar baz/\" ; renamed\n\
+             [submodule \"no-path\"]\n\
+             \turl = https://example.com/none.git\n",
+        );
+        assert_eq!(
+            submodules,
+            vec![
+                Submodule {
+                    name: "third_party/foo".to_string(),
+                    path: PathBuf::from("third_party/foo"),
+                },
+                Submodule {
+                    name: "bar".to_string(),
+                    path: PathBuf::from("libs/bar baz"),
+                },
+            ]
+        );
+    }
+}
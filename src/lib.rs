@@ -1,6 +1,22 @@
+pub mod cst;
+pub mod diagnostic;
+pub mod discovery;
+pub mod error;
+pub mod file_index;
+pub mod files;
+pub mod git;
+pub mod matcher;
+pub mod ownership;
 pub mod parser;
+pub mod render;
+pub mod reporter;
 pub mod validators;
 
 pub mod test_utils;
 
-pub use parser::{CodeOwnerRule, InvalidLine};
+pub use diagnostic::{Diagnostic, Severity};
+pub use discovery::{discover, discover_with, Discovery, Forge};
+pub use error::{Error, Result};
+pub use ownership::{Ownership, OwnershipResolver};
+pub use parser::{CodeOwnerRule, InvalidLine, InvalidReason, ParseOptions};
+pub use reporter::{NoopReporter, Reporter};
@@ -1,72 +1,591 @@
-use clap::Parser;
-use codeowners_validation::parser::parse_codeowners_file;
+use clap::{Parser, ValueEnum};
+use codeowners_validation::diagnostic::{Diagnostic, Severity};
+use codeowners_validation::discovery::{discover, discover_with, Forge};
+use codeowners_validation::error::{Error, Result};
+use codeowners_validation::files::{Excludes, FileOptions, FileSource, Manifest, SymlinkPolicy};
+use codeowners_validation::git::object::ObjectId;
+use codeowners_validation::git::repository::{Repository, TreeEntryKind};
+use codeowners_validation::parser::{parse_bytes_with_options, ParseOptions};
+use codeowners_validation::render::Renderer;
+use codeowners_validation::reporter::Reporter;
+use codeowners_validation::validators::exists::{rule_stats, MatchStats};
+use codeowners_validation::validators::limits::Limits;
+use codeowners_validation::validators::overbroad::{Breadth, DEFAULT_MAX_OVERRIDDEN_RULES};
 use codeowners_validation::validators::validator::{run_validator, ValidatorArgs};
-use std::{io, path::Path};
+use codeowners_validation::validators::ValidationContext;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fmt, fs};
 
 #[derive(Parser, Debug)]
 #[command(name = "codeowners-validation")]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Comma-separated list of checks: exists, duplicate_patterns
+    /// Comma-separated list of checks: exists, duplicate_patterns, no_owners, coverage, limits,
+    /// path_normalization, pattern_style, gitignored, overbroad
     #[arg(long, env = "INPUT_CHECKS", default_value = "all")]
     checks: String,
 
-    /// Path to CODEOWNERS file
-    #[arg(long, default_value = ".github/CODEOWNERS")]
-    path: String,
+    /// Patterns that are intentionally left without owners (comma-separated or repeated)
+    #[arg(long, env = "INPUT_ALLOW_UNOWNED", value_delimiter = ',')]
+    allow_unowned: Vec<String>,
+
+    /// Path to CODEOWNERS file, or `-` to read it from stdin. When omitted,
+    /// the file is found using the forge's lookup order
+    #[arg(long, env = "INPUT_PATH")]
+    path: Option<String>,
+
+    /// Root of the repository to validate. A discovered CODEOWNERS file is
+    /// looked up here, while --path stays relative to the current directory
+    #[arg(long, env = "INPUT_REPO_ROOT", value_name = "DIR")]
+    repo_root: Option<String>,
+
+    /// Forge whose CODEOWNERS lookup order applies
+    #[arg(long, env = "INPUT_FORGE", value_enum, default_value_t = ForgeChoice::Github)]
+    forge: ForgeChoice,
+
+    /// Where repository files come from: files tracked in the git index,
+    /// or everything in the working tree
+    #[arg(long, env = "INPUT_FILE_SOURCE", value_enum, default_value_t = FileSourceChoice::GitIndex)]
+    file_source: FileSourceChoice,
+
+    /// Validate a commit, branch or tag from the repository's object
+    /// database instead of the checked-out files. CODEOWNERS is read from
+    /// the same revision
+    #[arg(long, env = "INPUT_REV")]
+    rev: Option<String>,
+
+    /// Read the repository's files from a list of relative paths, one per
+    /// line or NUL-separated, instead of from the repository. `-` reads
+    /// the list from stdin
+    #[arg(long, env = "INPUT_FILES_FROM", value_name = "FILE")]
+    files_from: Option<String>,
+
+    /// Leave untracked files ignored by .gitignore out of every check that
+    /// looks at repository files. Tracked files always count
+    #[arg(long, env = "INPUT_EXCLUDE_IGNORED")]
+    exclude_ignored: bool,
+
+    /// Globs for paths to leave out of every check that looks at repository
+    /// files, e.g. vendor or bazel-out (comma-separated or repeated). A glob
+    /// without `/` matches at any depth; a leading `/` anchors it at the root
+    #[arg(long, env = "INPUT_EXCLUDE", value_delimiter = ',')]
+    exclude: Vec<String>,
+
+    /// How symlinks count: as files of their own, followed to their
+    /// targets, or not at all
+    #[arg(long, env = "INPUT_SYMLINKS", value_enum, default_value_t = SymlinkChoice::Entry)]
+    symlinks: SymlinkChoice,
+
+    /// List the files of checked-out submodules too, instead of treating
+    /// each submodule as a single entry
+    #[arg(long, env = "INPUT_DESCEND_SUBMODULES")]
+    descend_submodules: bool,
+
+    /// Print how many files each rule matches and owns, and its share of
+    /// the repository
+    #[arg(long, env = "INPUT_RULE_STATS")]
+    rule_stats: bool,
+
+    /// Number of matched paths to list per rule with --rule-stats
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    rule_stats_examples: usize,
+
+    /// Maximum line length in bytes for the limits check. No forge
+    /// documents one, so there is no limit by default
+    #[arg(long, env = "INPUT_MAX_LINE_LENGTH", value_name = "BYTES")]
+    max_line_length: Option<usize>,
+
+    /// Maximum number of rules for the limits check. No forge documents
+    /// one, so there is no limit by default
+    #[arg(long, env = "INPUT_MAX_RULES", value_name = "N")]
+    max_rules: Option<usize>,
+
+    /// Maximum number of owners on one rule for the limits check. No forge
+    /// documents one, so there is no limit by default
+    #[arg(long, env = "INPUT_MAX_OWNERS_PER_RULE", value_name = "N")]
+    max_owners_per_rule: Option<usize>,
+
+    /// Percentage of repository files, from 0 to 100, one rule may own
+    /// before the overbroad check flags it (no limit by default)
+    #[arg(long, env = "INPUT_MAX_RULE_SHARE", value_name = "PERCENT")]
+    max_rule_share: Option<f64>,
+
+    /// Number of files one rule may own before the overbroad check flags it
+    /// (no limit by default)
+    #[arg(long, env = "INPUT_MAX_RULE_FILES", value_name = "N")]
+    max_rule_files: Option<usize>,
+
+    /// Number of earlier rules with other owners that one rule may take
+    /// files from before the overbroad check flags it, or `off`
+    #[arg(
+        long,
+        env = "INPUT_MAX_OVERRIDDEN_RULES",
+        value_name = "N",
+        default_value_t = Threshold(Some(DEFAULT_MAX_OVERRIDDEN_RULES))
+    )]
+    max_overridden_rules: Threshold,
+
+    /// When to use colored output (honours NO_COLOR when set to auto)
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Treat warnings as errors when deciding the exit code
+    #[arg(long, env = "INPUT_WARNINGS_AS_ERRORS")]
+    warnings_as_errors: bool,
+
+    /// How to report lines that are not valid UTF-8
+    #[arg(long, value_enum, default_value_t = Level::Error)]
+    non_utf8: Level,
+
+    /// How to report a UTF-8 byte order mark at the start of the file
+    #[arg(long, value_enum, default_value_t = Level::Warning)]
+    bom: Level,
+
+    /// How to report CRLF line endings
+    #[arg(long, value_enum, default_value_t = Level::Warning)]
+    crlf: Level,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Level {
+    Error,
+    Warning,
+}
+
+impl From<Level> for Severity {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Error => Severity::Error,
+            Level::Warning => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ForgeChoice {
+    Github,
+    Gitlab,
+}
+
+impl From<ForgeChoice> for Forge {
+    fn from(choice: ForgeChoice) -> Self {
+        match choice {
+            ForgeChoice::Github => Forge::GitHub,
+            ForgeChoice::Gitlab => Forge::GitLab,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum FileSourceChoice {
+    GitIndex,
+    Worktree,
+}
+
+impl From<FileSourceChoice> for FileSource<'_> {
+    fn from(choice: FileSourceChoice) -> Self {
+        match choice {
+            FileSourceChoice::GitIndex => FileSource::GitIndex,
+            FileSourceChoice::Worktree => FileSource::Worktree,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum SymlinkChoice {
+    Entry,
+    Follow,
+    Skip,
+}
+
+impl From<SymlinkChoice> for SymlinkPolicy {
+    fn from(choice: SymlinkChoice) -> Self {
+        match choice {
+            SymlinkChoice::Entry => SymlinkPolicy::Entry,
+            SymlinkChoice::Follow => SymlinkPolicy::Follow,
+            SymlinkChoice::Skip => SymlinkPolicy::Skip,
+        }
+    }
+}
+
+/// A count limit that `off` turns off.
+#[derive(Clone, Copy, Debug)]
+struct Threshold(Option<usize>);
+
+impl FromStr for Threshold {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "off" => Ok(Threshold(None)),
+            value => value
+                .parse()
+                .map(|limit| Threshold(Some(limit)))
+                .map_err(|_| format!("expected a number or `off`, got `{}`", value)),
+        }
+    }
+}
+
+impl fmt::Display for Threshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(limit) => write!(f, "{}", limit),
+            None => f.write_str("off"),
+        }
+    }
+}
+
+/// Process exit codes. Keep in sync with the README and entrypoint.sh.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Exit {
+    /// No findings at error severity
+    Clean = 0,
+    /// Findings at error severity (or warnings with --warnings-as-errors)
+    Findings = 1,
+    /// Invalid command line or configuration
+    Config = 2,
+    /// The CODEOWNERS file does not exist
+    NotFound = 3,
+    /// The tool could not complete a check
+    Internal = 4,
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// A revision being validated instead of the working tree.
+struct Revision<'a> {
+    name: &'a str,
+    repo: Repository,
+    tree: ObjectId,
+}
+
+impl Revision<'_> {
+    fn is_file(&self, path: &Path) -> bool {
+        self.repo
+            .find_entry(&self.tree, path)
+            .ok()
+            .flatten()
+            .is_some_and(|entry| entry.kind == TreeEntryKind::File)
+    }
 }
 
-fn main() -> io::Result<()> {
+/// Prints validation progress and diagnostics to the terminal.
+struct TerminalReporter<'a> {
+    renderer: Renderer<'a>,
+}
+
+impl Reporter for TerminalReporter<'_> {
+    fn validator_finished(&self, name: &str, elapsed: Duration, issues: usize) {
+        println!(
+            "✓ {} validation completed in {:?} ({} issues found)",
+            name, elapsed, issues
+        );
+    }
+
+    fn diagnostic(&self, diagnostic: &Diagnostic) {
+        println!("{}", self.renderer.render(diagnostic));
+    }
+
+    fn error(&self, validator: &str, error: &dyn std::error::Error) {
+        eprintln!("❌ Error during '{}' validation: {}", validator, error);
+    }
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    let validator_args = ValidatorArgs::from_env(&cli.checks);
-    let path = Path::new(&cli.path);
+    let exit = match run(&cli) {
+        Ok(exit) => exit,
+        Err(Error::Config(message)) => {
+            eprintln!("❌ Invalid configuration: {}", message);
+            Exit::Config
+        }
+        Err(err) => {
+            eprintln!("❌ Internal error: {}", err);
+            Exit::Internal
+        }
+    };
 
-    if !path.exists() {
-        eprintln!("❌ CODEOWNERS file not found at {:?}", path);
-        return Err(io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("CODEOWNERS file not found at {:?}", path),
+    exit.into()
+}
+
+fn run(cli: &Cli) -> Result<Exit> {
+    if let Some(share) = cli
+        .max_rule_share
+        .filter(|share| !(0.0..=100.0).contains(share))
+    {
+        return Err(Error::Config(format!(
+            "--max-rule-share must be a percentage from 0 to 100, got {}",
+            share
+        )));
+    }
+    let validator_args = ValidatorArgs {
+        allow_unowned: cli.allow_unowned.clone(),
+        limit_overrides: Limits {
+            max_line_length: cli.max_line_length,
+            max_rules: cli.max_rules,
+            max_owners_per_rule: cli.max_owners_per_rule,
+            ..Limits::default()
+        },
+        breadth: Breadth {
+            max_share: cli.max_rule_share,
+            max_files: cli.max_rule_files,
+            max_overridden_rules: cli.max_overridden_rules.0,
+        },
+        ..ValidatorArgs::from_env(&cli.checks)?
+    };
+    let options = ParseOptions {
+        non_utf8: cli.non_utf8.into(),
+        bom: cli.bom.into(),
+        crlf: cli.crlf.into(),
+    };
+
+    let files_from = cli.files_from.as_deref().filter(|path| !path.is_empty());
+    let path = cli.path.as_deref().filter(|path| !path.is_empty());
+    if files_from.is_some() && cli.rev.as_deref().is_some_and(|rev| !rev.is_empty()) {
+        return Err(Error::Config(
+            "--files-from and --rev both say where files come from; use one".to_string(),
         ));
     }
+    if files_from == Some("-") && path == Some("-") {
+        return Err(Error::Config(
+            "--files-from and --path cannot both read from stdin".to_string(),
+        ));
+    }
+    let manifest = files_from.map(read_manifest).transpose()?;
+
+    let file_options = FileOptions {
+        exclude_ignored: cli.exclude_ignored,
+        exclude: cli
+            .exclude
+            .iter()
+            .filter(|glob| !glob.is_empty())
+            .cloned()
+            .collect(),
+        symlinks: cli.symlinks.into(),
+        descend_submodules: cli.descend_submodules,
+    };
+    Excludes::new(&file_options.exclude)?;
+
+    let repo_root = cli
+        .repo_root
+        .as_deref()
+        .filter(|root| !root.is_empty())
+        .map(Path::new);
+    let root = repo_root.unwrap_or(Path::new("."));
+    if !root.is_dir() {
+        return Err(Error::Config(format!(
+            "--repo-root {} is not a directory",
+            root.display()
+        )));
+    }
 
-    let (rules, invalid_lines) = match parse_codeowners_file(&cli.path) {
-        Ok((rules, invalid_lines)) => (rules, invalid_lines),
-        Err(e) => {
-            eprintln!("❌ Error parsing CODEOWNERS file: {}", e);
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Failed to parse CODEOWNERS file: {}", e),
-            ));
+    let forge = Forge::from(cli.forge);
+    let revision = match cli.rev.as_deref().filter(|rev| !rev.is_empty()) {
+        Some(name) => {
+            let repo = Repository::open(root)
+                .map_err(|err| Error::Config(format!("--rev needs a git repository: {}", err)))?;
+            let tree = repo.resolve_tree(name)?;
+            println!("🔖 Validating revision {} (tree {})", name, tree);
+            Some(Revision { name, repo, tree })
         }
+        None => None,
+    };
+    let discovery = match &revision {
+        Some(revision) => discover_with(forge, |path| revision.is_file(path)),
+        None => discover(root, forge),
     };
 
-    if !invalid_lines.is_empty() {
-        eprintln!("⚠️  Invalid lines found:");
-        for line in invalid_lines {
-            eprintln!(" - Line {}: {}", line.line_number, line.content);
+    let (display_path, bytes) = match path {
+        Some("-") => {
+            let mut bytes = Vec::new();
+            io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|err| Error::io("<stdin>", err))?;
+            ("<stdin>".to_string(), bytes)
         }
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Invalid lines found in the CODEOWNERS file",
-        ));
+        requested => {
+            let path = match requested {
+                Some(path) => PathBuf::from(path),
+                None => {
+                    let Some(path) = &discovery.effective else {
+                        eprintln!(
+                            "❌ No CODEOWNERS file found (looked for {})",
+                            forge.locations().join(", ")
+                        );
+                        return Ok(Exit::NotFound);
+                    };
+                    let path = match (repo_root, &revision) {
+                        (Some(repo_root), None) => repo_root.join(path),
+                        _ => path.clone(),
+                    };
+                    println!("📄 Using CODEOWNERS file at {}", path.display());
+                    path
+                }
+            };
+            let Some(bytes) = read_codeowners(revision.as_ref(), &path)? else {
+                eprintln!("❌ CODEOWNERS file not found at {:?}", path);
+                return Ok(Exit::NotFound);
+            };
+            let display_path = match &revision {
+                Some(revision) => format!("{}:{}", revision.name, path.display()),
+                None => path.display().to_string(),
+            };
+            (display_path, bytes)
+        }
+    };
+
+    let (rules, invalid_lines) = parse_bytes_with_options(&bytes, &options);
+
+    // Parser columns are relative to the line after the BOM is stripped
+    let source = String::from_utf8_lossy(bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes));
+    let reporter = TerminalReporter {
+        renderer: Renderer::new(&display_path, &source, cli.color.enabled()),
+    };
+
+    let mut diagnostics = Vec::new();
+    if let Some(effective) = &discovery.effective {
+        diagnostics.extend(
+            discovery
+                .shadowed
+                .iter()
+                .map(|shadowed| Diagnostic::shadowed_codeowners(shadowed, effective, forge)),
+        );
+    }
+    diagnostics.extend(invalid_lines.iter().map(Diagnostic::from));
+    for diagnostic in &diagnostics {
+        reporter.diagnostic(diagnostic);
+    }
+
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        eprintln!("❌ Invalid lines found in the CODEOWNERS file");
+        return Ok(Exit::Findings);
+    }
+
+    let ctx = ValidationContext::new(&rules)
+        .with_source(&bytes)
+        .with_repo_root(root)
+        .with_forge(forge)
+        .with_file_source(match (&revision, &manifest) {
+            (Some(revision), _) => FileSource::Revision(revision.tree),
+            (None, Some(manifest)) => FileSource::Manifest(manifest),
+            (None, None) => cli.file_source.into(),
+        })
+        .with_file_options(&file_options);
+    if cli.rule_stats {
+        // The stats list every file anyway, so the checks share that list
+        ctx.files()?;
+    }
+    diagnostics.extend(run_validator(&validator_args, &ctx, &reporter)?);
+
+    if cli.rule_stats {
+        let stats = rule_stats(ctx.rules, ctx.files()?, cli.rule_stats_examples)?;
+        print_rule_stats(&stats);
     }
 
-    let failed_rules = run_validator(&validator_args, &rules);
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+
+    if errors > 0 || (cli.warnings_as_errors && warnings > 0) {
+        eprintln!(
+            "❌ CODEOWNERS validation failed: {} error(s), {} warning(s)",
+            errors, warnings
+        );
+        return Ok(Exit::Findings);
+    }
 
-    if !failed_rules.is_empty() {
-        eprintln!("❌ The following rules failed:\n");
-        for (validator, rule) in &failed_rules {
-            eprintln!("Validator: {}", validator);
-            eprintln!("  Pattern: {}", rule.pattern);
-            eprintln!("    Rule: {}", rule.original_path);
-            eprintln!("    Owners: {:?}", rule.owners);
-            eprintln!();
+    if warnings > 0 {
+        println!(
+            "✅ CODEOWNERS validation passed with {} warning(s).",
+            warnings
+        );
+    } else {
+        println!("✅ CODEOWNERS validation passed.");
+    }
+    Ok(Exit::Clean)
+}
+
+/// Prints one line per rule: its line number, matched and effective file
+/// counts, share of the repository and pattern, then any example paths.
+fn print_rule_stats(stats: &MatchStats) {
+    println!("📊 Rule statistics ({} files)", stats.files);
+    println!(
+        "{:>6} {:>9} {:>9} {:>7}  pattern",
+        "line", "matched", "effective", "share"
+    );
+    for rule in &stats.rules {
+        println!(
+            "{:>6} {:>9} {:>9} {:>6.1}%  {}",
+            rule.rule.line_number,
+            rule.matched,
+            rule.effective,
+            stats.share(rule) * 100.0,
+            rule.rule.original_path
+        );
+        for example in &rule.examples {
+            println!("{:>36}{}", "", example.display());
         }
+    }
+}
 
-        return Err(io::Error::other("Some rules failed validation"));
+/// Reads the CODEOWNERS file from the revision, or from disk. `None` when
+/// there is no such file.
+fn read_codeowners(revision: Option<&Revision>, path: &Path) -> Result<Option<Vec<u8>>> {
+    match revision {
+        Some(revision) => revision.repo.read_file(&revision.tree, path),
+        None if path.exists() => fs::read(path).map(Some).map_err(|err| Error::io(path, err)),
+        None => Ok(None),
     }
+}
 
-    println!("✅ CODEOWNERS validation passed.");
-    Ok(())
+/// Reads the file list given with `--files-from`.
+fn read_manifest(path: &str) -> Result<Manifest> {
+    let bytes = match path {
+        "-" => {
+            let mut bytes = Vec::new();
+            io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|err| Error::io("<stdin>", err))?;
+            bytes
+        }
+        path => fs::read(path).map_err(|err| Error::io(path, err))?,
+    };
+    let manifest = Manifest::parse(&bytes)?;
+    let display_path = if path == "-" { "<stdin>" } else { path };
+    println!("🗂️  Using {} paths from {}", manifest.len(), display_path);
+    Ok(manifest)
 }
@@ -0,0 +1,271 @@
+use crate::error::{Error, Result};
+use crate::parser::CodeOwnerRule;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::{Path, PathBuf};
+
+/// How a path matched a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The pattern names the path itself
+    Path,
+    /// The path is inside a directory the pattern names
+    Contents,
+}
+
+/// Builds the globs that together match everything a CODEOWNERS pattern
+/// covers. `pattern` is the normalized pattern and `original_path` the
+/// pattern as written, which decides anchoring and directory semantics.
+///
+/// The rules are GitHub's, which follow `.gitignore`: `*` and `?` do not
+/// match `/`, a pattern with a `/` at the start or in the middle is
+/// relative to the repository root, and any other pattern matches at any
+/// depth.
+pub(crate) fn rule_globs(
+    pattern: &str,
+    original_path: &str,
+) -> Result<Vec<(Glob, MatchKind)>, globset::Error> {
+    let is_anchored = original_path.starts_with('/') || pattern.contains('/');
+    let pattern = match is_anchored {
+        true => pattern.to_string(),
+        false => format!("**/{}", pattern),
+    };
+
+    let mut globs = vec![(glob(&pattern)?, MatchKind::Path)];
+    // A pattern that names a directory, with or without a trailing `/`,
+    // also covers everything inside it: /config → config/**. GitHub leaves
+    // the nested files of `docs/*` to other rules, though.
+    if !pattern.ends_with("/*") {
+        globs.push((glob(&format!("{}/**", pattern))?, MatchKind::Contents));
+    }
+    Ok(globs)
+}
+
+fn glob(pattern: &str) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(pattern).literal_separator(true).build()
+}
+
+/// The directories at the start of `glob` that contain no glob syntax.
+/// Every path the glob matches starts with them.
+fn literal_prefix(glob: &str) -> &Path {
+    let literal = match glob.find(['*', '?', '[', '{', '\\']) {
+        // Only whole segments count; `src/lib*` starts in `src`
+        Some(idx) => glob[..idx].rsplit_once('/').map_or("", |(dirs, _)| dirs),
+        None => glob,
+    };
+    Path::new(literal)
+}
+
+/// The directories `a` and `b` both start with.
+fn common_prefix(a: &Path, b: &Path) -> PathBuf {
+    a.components()
+        .zip(b.components())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a)
+        .collect()
+}
+
+/// Matches repository paths against a list of rules in a single pass.
+///
+/// Rule indices refer to the order the rules were given in.
+#[derive(Debug, Clone)]
+pub struct RuleMatcher {
+    globset: GlobSet,
+    glob_to_rule: Vec<(usize, MatchKind)>,
+    /// Per rule, the directory every path it matches is in or above
+    prefixes: Vec<PathBuf>,
+}
+
+impl RuleMatcher {
+    pub fn new<'a>(rules: impl IntoIterator<Item = &'a CodeOwnerRule>) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        let mut glob_to_rule = Vec::new();
+        let mut prefixes = Vec::new();
+
+        for (rule_idx, rule) in rules.into_iter().enumerate() {
+            let globs = rule_globs(&rule.pattern, &rule.original_path).map_err(|source| {
+                Error::InvalidGlob {
+                    pattern: rule.pattern.clone(),
+                    source,
+                }
+            })?;
+            prefixes.push(
+                globs
+                    .iter()
+                    .map(|(glob, _)| literal_prefix(glob.glob()).to_path_buf())
+                    .reduce(|a, b| common_prefix(&a, &b))
+                    .unwrap_or_default(),
+            );
+            for (glob, kind) in globs {
+                builder.add(glob);
+                glob_to_rule.push((rule_idx, kind));
+            }
+        }
+
+        let globset = builder.build().map_err(|source| Error::InvalidGlob {
+            pattern: source.glob().unwrap_or_default().to_string(),
+            source,
+        })?;
+
+        Ok(RuleMatcher {
+            globset,
+            glob_to_rule,
+            prefixes,
+        })
+    }
+
+    /// Indices of every rule matching `path`, in ascending order.
+    pub fn matches(&self, path: impl AsRef<Path>) -> Vec<usize> {
+        let mut rules: Vec<usize> = self
+            .globset
+            .matches(path)
+            .into_iter()
+            .map(|glob_idx| self.glob_to_rule[glob_idx].0)
+            .collect();
+        rules.dedup();
+        rules
+    }
+
+    /// Like `matches`, but also says how each rule matched. A rule can
+    /// appear twice if it matches both ways.
+    pub fn matches_with_kind(&self, path: impl AsRef<Path>) -> Vec<(usize, MatchKind)> {
+        self.globset
+            .matches(path)
+            .into_iter()
+            .map(|glob_idx| self.glob_to_rule[glob_idx])
+            .collect()
+    }
+
+    /// Index of the last rule matching `path`, which is the one that
+    /// decides its owners.
+    pub fn last_match(&self, path: impl AsRef<Path>) -> Option<usize> {
+        self.globset
+            .matches(path)
+            .into_iter()
+            .map(|glob_idx| self.glob_to_rule[glob_idx].0)
+            .max()
+    }
+
+    /// The literal path a rule's pattern starts with, e.g. `services/api`
+    /// for `/services/api/**/*.rs`. Paths outside it and not above it
+    /// cannot match the rule. Patterns that match anywhere have an empty
+    /// prefix.
+    pub fn literal_prefix(&self, rule: usize) -> &Path {
+        &self.prefixes[rule]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(original: &str) -> CodeOwnerRule {
+        CodeOwnerRule {
+            pattern: original.trim_matches('/').to_string(),
+            original_path: original.to_string(),
+            owners: vec!["@team".to_string()],
+            line_number: 0,
+        }
+    }
+
+    #[test]
+    fn follows_anchoring_and_directory_semantics() {
+        let rules = [
+            rule("/docs/"),
+            rule("lib/"),
+            rule("/README.md"),
+            rule("*.rs"),
+        ];
+        let matcher = RuleMatcher::new(&rules).unwrap();
+
+        assert_eq!(matcher.matches("docs/guide.md"), vec![0]);
+        assert!(matcher.matches("src/docs/guide.md").is_empty());
+        assert_eq!(matcher.matches("a/lib/x.txt"), vec![1]);
+        assert!(matcher.matches("a/README.md").is_empty());
+        assert_eq!(matcher.matches("lib/main.rs"), vec![1, 3]);
+    }
+
+    #[test]
+    fn stars_stay_within_one_directory() {
+        let rules = [
+            rule("/docs/*"),
+            rule("docs/*.md"),
+            rule("src/config.json"),
+            rule("*.md"),
+        ];
+        let matcher = RuleMatcher::new(&rules).unwrap();
+
+        assert_eq!(matcher.matches("docs/index.md"), vec![0, 1, 3]);
+        // Nested files fall back to other rules, as on GitHub
+        assert_eq!(matcher.matches("docs/guide/a.md"), vec![3]);
+        assert!(matcher.matches("lib/docs/a.txt").is_empty());
+        // A slash in the middle anchors the pattern at the root
+        assert_eq!(matcher.matches("src/config.json"), vec![2]);
+        assert!(matcher.matches("app/src/config.json").is_empty());
+    }
+
+    #[test]
+    fn tells_paths_from_directory_contents() {
+        let rules = [rule("/docs/"), rule("docs")];
+        let matcher = RuleMatcher::new(&rules).unwrap();
+
+        assert_eq!(
+            matcher.matches_with_kind("docs"),
+            vec![(0, MatchKind::Path), (1, MatchKind::Path)]
+        );
+        // With or without the trailing `/`, a directory's contents match
+        assert_eq!(
+            matcher.matches_with_kind("docs/guide.md"),
+            vec![(0, MatchKind::Contents), (1, MatchKind::Contents)]
+        );
+    }
+
+    #[test]
+    fn last_match_wins() {
+        let rules = [rule("*"), rule("/src/"), rule("*.md")];
+        let matcher = RuleMatcher::new(&rules).unwrap();
+
+        assert_eq!(matcher.last_match("src/main.rs"), Some(1));
+        assert_eq!(matcher.last_match("src/README.md"), Some(2));
+        assert_eq!(matcher.last_match("Cargo.toml"), Some(0));
+    }
+
+    #[test]
+    fn finds_the_literal_prefix_of_each_rule() {
+        let rules = [
+            rule("/services/payments/"),
+            rule("/services/api/**/*.rs"),
+            rule("/src/lib*.rs"),
+            rule("docs/*.md"),
+            rule("/README.md"),
+            rule("lib/"),
+            rule("*.rs"),
+        ];
+        let matcher = RuleMatcher::new(&rules).unwrap();
+
+        let prefixes: Vec<&Path> = (0..rules.len())
+            .map(|idx| matcher.literal_prefix(idx))
+            .collect();
+        assert_eq!(
+            prefixes,
+            [
+                "services/payments",
+                "services/api",
+                "src",
+                "docs",
+                "README.md",
+                "",
+                "",
+            ]
+            .map(Path::new)
+        );
+    }
+
+    #[test]
+    fn reports_the_rule_behind_an_invalid_glob() {
+        let result = RuleMatcher::new(&[rule("/src/[")]);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidGlob { pattern, .. }) if pattern == "src/["
+        ));
+    }
+}
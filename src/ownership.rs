@@ -0,0 +1,102 @@
+use crate::error::Result;
+use crate::matcher::RuleMatcher;
+use crate::parser::CodeOwnerRule;
+use std::path::Path;
+
+/// Who owns a path, according to the last rule that matches it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ownership<'a> {
+    /// The deciding rule lists owners
+    Owned(&'a CodeOwnerRule),
+    /// The deciding rule has no owners, so ownership was removed on purpose
+    ExplicitlyUnowned(&'a CodeOwnerRule),
+    /// No rule matches the path
+    Unowned,
+}
+
+impl<'a> Ownership<'a> {
+    /// The rule that decided the ownership, if any.
+    pub fn rule(&self) -> Option<&'a CodeOwnerRule> {
+        match self {
+            Ownership::Owned(rule) | Ownership::ExplicitlyUnowned(rule) => Some(rule),
+            Ownership::Unowned => None,
+        }
+    }
+
+    pub fn owners(&self) -> &'a [String] {
+        self.rule().map_or(&[], |rule| &rule.owners)
+    }
+}
+
+/// Resolves the owners of repository paths the way GitHub does: the last
+/// matching rule wins, even when it has no owners.
+#[derive(Debug)]
+pub struct OwnershipResolver<'a> {
+    rules: &'a [CodeOwnerRule],
+    matcher: RuleMatcher,
+}
+
+impl<'a> OwnershipResolver<'a> {
+    pub fn new(rules: &'a [CodeOwnerRule]) -> Result<Self> {
+        Ok(OwnershipResolver {
+            rules,
+            matcher: RuleMatcher::new(rules)?,
+        })
+    }
+
+    /// `path` is relative to the repository root.
+    pub fn resolve(&self, path: impl AsRef<Path>) -> Ownership<'a> {
+        match self.matcher.last_match(path) {
+            Some(idx) if self.rules[idx].is_unowned() => {
+                Ownership::ExplicitlyUnowned(&self.rules[idx])
+            }
+            Some(idx) => Ownership::Owned(&self.rules[idx]),
+            None => Ownership::Unowned,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_str;
+
+    #[test]
+    fn last_matching_rule_decides() {
+        let (rules, _) = parse_str("* @everyone\n/src/ @core\n/src/generated/\n");
+        let resolver = OwnershipResolver::new(&rules).unwrap();
+
+        assert_eq!(resolver.resolve("README.md").owners(), ["@everyone"]);
+        assert_eq!(resolver.resolve("src/lib.rs").owners(), ["@core"]);
+
+        let generated = resolver.resolve("src/generated/api.rs");
+        assert!(matches!(generated, Ownership::ExplicitlyUnowned(rule) if rule.line_number == 3));
+        assert!(generated.owners().is_empty());
+    }
+
+    #[test]
+    fn single_star_leaves_nested_files_to_earlier_rules() {
+        let (rules, _) = parse_str("* @everyone\n/docs/* @docs\n");
+        let resolver = OwnershipResolver::new(&rules).unwrap();
+
+        assert_eq!(resolver.resolve("docs/index.md").owners(), ["@docs"]);
+        assert_eq!(resolver.resolve("docs/guide/a.md").owners(), ["@everyone"]);
+    }
+
+    #[test]
+    fn directory_names_own_their_contents_without_a_trailing_slash() {
+        let (rules, _) = parse_str("* @all\n/config @cfg\napps @apps\n");
+        let resolver = OwnershipResolver::new(&rules).unwrap();
+
+        assert_eq!(resolver.resolve("config/app.toml").owners(), ["@cfg"]);
+        assert_eq!(resolver.resolve("web/apps/main.ts").owners(), ["@apps"]);
+        assert_eq!(resolver.resolve("lib/config/app.toml").owners(), ["@all"]);
+    }
+
+    #[test]
+    fn unmatched_paths_are_unowned() {
+        let (rules, _) = parse_str("/src/ @core\n");
+        let resolver = OwnershipResolver::new(&rules).unwrap();
+        assert_eq!(resolver.resolve("docs/guide.md"), Ownership::Unowned);
+    }
+}
@@ -1,114 +1,441 @@
+use crate::diagnostic::Severity;
+use crate::matcher::rule_globs;
+use std::borrow::Cow;
+use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
+use std::ops::Range;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct CodeOwnerRule {
-    pub pattern: String, // Normalized pattern (no leading/trailing /)
+    pub pattern: String, // Normalized, unescaped pattern (no leading/trailing /)
     pub owners: Vec<String>,
     pub original_path: String, // Original path from file (with / if present)
+    pub line_number: usize,    // 1-based line in the CODEOWNERS file
+}
+
+impl CodeOwnerRule {
+    /// Whether this rule lists no owners. Such a rule is valid: it removes
+    /// ownership from the files it matches, overriding earlier rules.
+    pub fn is_unowned(&self) -> bool {
+        self.owners.is_empty()
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InvalidLine {
     pub line_number: usize,
     pub content: String,
+    pub reason: InvalidReason,
+    pub column: usize, // 0-based byte offset of the problem within `content`
+    pub severity: Severity,
+}
+
+/// Why a line was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidReason {
+    /// The pattern is nothing but slashes, e.g. `/`
+    EmptyPattern,
+    /// A `[` without a matching `]`
+    UnclosedCharacterClass { glob_error: String },
+    /// CODEOWNERS does not support `!` to negate a pattern
+    UnsupportedNegation,
+    /// Any other pattern globset refuses to compile
+    InvalidGlob { glob_error: String },
+    /// The line is not valid UTF-8; the offset is that of the first bad byte
+    NonUtf8 { byte_offset: usize },
+    /// The file starts with a UTF-8 byte order mark
+    ByteOrderMark,
+    /// The file uses `\r\n` line endings; reported on the first such line
+    CrlfLineEnding,
+}
+
+impl InvalidReason {
+    /// The globset error behind this reason, if any.
+    pub fn glob_error(&self) -> Option<&str> {
+        match self {
+            InvalidReason::UnclosedCharacterClass { glob_error }
+            | InvalidReason::InvalidGlob { glob_error } => Some(glob_error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for InvalidReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidReason::EmptyPattern => f.write_str("pattern is empty after trimming slashes"),
+            InvalidReason::UnclosedCharacterClass { .. } => f.write_str("unclosed character class"),
+            InvalidReason::UnsupportedNegation => f.write_str("negated patterns are not supported"),
+            InvalidReason::InvalidGlob { .. } => f.write_str("invalid glob pattern"),
+            InvalidReason::NonUtf8 { byte_offset } => {
+                write!(f, "invalid UTF-8 at byte offset {}", byte_offset)
+            }
+            InvalidReason::ByteOrderMark => f.write_str("file starts with a UTF-8 byte order mark"),
+            InvalidReason::CrlfLineEnding => f.write_str("file uses CRLF line endings"),
+        }
+    }
+}
+
+impl fmt::Display for InvalidLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line_number,
+            self.column + 1,
+            self.reason
+        )?;
+        if let Some(glob_error) = self.reason.glob_error() {
+            write!(f, " ({})", glob_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// How to treat encoding problems the parser can recover from.
+///
+/// Each setting decides whether the problem is reported as an error, which
+/// fails validation, or as a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Lines that are not valid UTF-8. As a warning, the line is decoded
+    /// lossily and still used as a rule; as an error, it is dropped.
+    pub non_utf8: Severity,
+    /// A UTF-8 byte order mark at the start of the file. It is always
+    /// stripped before parsing.
+    pub bom: Severity,
+    /// `\r\n` line endings. The `\r` is always stripped before parsing.
+    pub crlf: Severity,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            non_utf8: Severity::Error,
+            bom: Severity::Warning,
+            crlf: Severity::Warning,
+        }
+    }
 }
 
 pub fn parse_codeowners_file(
     file_path: &str,
+) -> io::Result<(Vec<CodeOwnerRule>, Vec<InvalidLine>)> {
+    parse_codeowners_file_with_options(file_path, &ParseOptions::default())
+}
+
+pub fn parse_codeowners_file_with_options(
+    file_path: &str,
+    options: &ParseOptions,
 ) -> io::Result<(Vec<CodeOwnerRule>, Vec<InvalidLine>)> {
     let file = File::open(file_path)?;
     let reader = BufReader::with_capacity(64 * 1024, file);
+    parse_lines(reader, options)
+}
 
-    let mut rules = Vec::with_capacity(1000);
-    let mut invalid_lines = Vec::new();
+/// Parses CODEOWNERS content from any buffered reader, e.g. stdin or a git
+/// blob stream.
+pub fn parse_reader<R: BufRead>(reader: R) -> io::Result<(Vec<CodeOwnerRule>, Vec<InvalidLine>)> {
+    parse_lines(reader, &ParseOptions::default())
+}
 
-    for (line_number, line_result) in reader.lines().enumerate() {
-        if let Ok(line) = line_result {
-            let trimmed_line = line.trim();
-            if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
-                // Skip empty lines and comments
-                continue;
-            }
+pub fn parse_reader_with_options<R: BufRead>(
+    reader: R,
+    options: &ParseOptions,
+) -> io::Result<(Vec<CodeOwnerRule>, Vec<InvalidLine>)> {
+    parse_lines(reader, options)
+}
 
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.is_empty() {
-                continue;
-            }
+/// Parses CODEOWNERS content that is already in memory. Unlike the file and
+/// reader variants this cannot fail.
+pub fn parse_bytes(content: &[u8]) -> (Vec<CodeOwnerRule>, Vec<InvalidLine>) {
+    parse_bytes_with_options(content, &ParseOptions::default())
+}
+
+pub fn parse_bytes_with_options(
+    content: &[u8],
+    options: &ParseOptions,
+) -> (Vec<CodeOwnerRule>, Vec<InvalidLine>) {
+    let mut parser = LineParser::new(options);
+    for line in content.split_inclusive(|&byte| byte == b'\n') {
+        parser.push(line);
+    }
+    parser.finish()
+}
 
-            let pattern = parts[0].trim_matches('/').to_string();
-            let owners = parts[1..].iter().map(|s| s.to_string()).collect();
-            let original_path = parts[0].to_string();
+pub fn parse_str(content: &str) -> (Vec<CodeOwnerRule>, Vec<InvalidLine>) {
+    parse_bytes(content.as_bytes())
+}
 
-            // Basic validation - ensure pattern is not empty after trimming
-            if pattern.is_empty() {
-                let invalid_line = InvalidLine {
-                    line_number: line_number + 1,
-                    content: line,
-                };
-                invalid_lines.push(invalid_line);
-                continue;
+pub fn parse_str_with_options(
+    content: &str,
+    options: &ParseOptions,
+) -> (Vec<CodeOwnerRule>, Vec<InvalidLine>) {
+    parse_bytes_with_options(content.as_bytes(), options)
+}
+
+fn parse_lines<R: BufRead>(
+    mut reader: R,
+    options: &ParseOptions,
+) -> io::Result<(Vec<CodeOwnerRule>, Vec<InvalidLine>)> {
+    let mut parser = LineParser::new(options);
+    let mut buf = Vec::with_capacity(256);
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        parser.push(&buf);
+    }
+    Ok(parser.finish())
+}
+
+// Works on raw bytes so that lines which fail to decode are reported
+// instead of silently disappearing
+struct LineParser<'a> {
+    options: &'a ParseOptions,
+    rules: Vec<CodeOwnerRule>,
+    invalid_lines: Vec<InvalidLine>,
+    line_number: usize,
+    seen_crlf: bool,
+}
+
+impl<'a> LineParser<'a> {
+    fn new(options: &'a ParseOptions) -> Self {
+        LineParser {
+            options,
+            rules: Vec::with_capacity(1000),
+            invalid_lines: Vec::new(),
+            line_number: 0,
+            seen_crlf: false,
+        }
+    }
+
+    /// Parses the next line, with or without its trailing newline.
+    fn push(&mut self, line: &[u8]) {
+        let options = self.options;
+        self.line_number += 1;
+        let line_number = self.line_number;
+
+        let mut bytes = line.strip_suffix(b"\n").unwrap_or(line);
+        let mut issues = Vec::new();
+
+        if line_number == 1 {
+            if let Some(stripped) = bytes.strip_prefix(UTF8_BOM) {
+                bytes = stripped;
+                issues.push((InvalidReason::ByteOrderMark, 0, options.bom));
+            }
+        }
+        if let Some(stripped) = bytes.strip_suffix(b"\r") {
+            bytes = stripped;
+            if !self.seen_crlf {
+                self.seen_crlf = true;
+                issues.push((InvalidReason::CrlfLineEnding, bytes.len(), options.crlf));
             }
+        }
 
-            // Check for invalid glob patterns
-            if validate_pattern(&pattern, &original_path).is_err() {
-                let invalid_line = InvalidLine {
-                    line_number: line_number + 1,
-                    content: line,
-                };
-                invalid_lines.push(invalid_line);
-                continue;
+        let line = match std::str::from_utf8(bytes) {
+            Ok(line) => Cow::Borrowed(line),
+            Err(err) => {
+                let line = String::from_utf8_lossy(bytes);
+                // Comments carry no rules, so their encoding doesn't matter
+                if !is_blank_or_comment(&line) {
+                    let byte_offset = err.valid_up_to();
+                    issues.push((
+                        InvalidReason::NonUtf8 { byte_offset },
+                        byte_offset,
+                        options.non_utf8,
+                    ));
+                }
+                line
             }
+        };
 
-            let rule = CodeOwnerRule {
-                pattern,
-                owners,
-                original_path,
-            };
+        let mut drop_line = false;
+        for (reason, column, severity) in issues {
+            drop_line |=
+                matches!(reason, InvalidReason::NonUtf8 { .. }) && severity == Severity::Error;
+            self.invalid_lines.push(InvalidLine {
+                line_number,
+                content: line.to_string(),
+                reason,
+                column,
+                severity,
+            });
+        }
+        if drop_line {
+            return;
+        }
 
-            rules.push(rule);
+        match parse_line(&line, line_number) {
+            Some(Ok(rule)) => self.rules.push(rule),
+            Some(Err((reason, column))) => self.invalid_lines.push(InvalidLine {
+                line_number,
+                content: line.into_owned(),
+                reason,
+                column,
+                severity: Severity::Error,
+            }),
+            None => (),
         }
     }
 
-    rules.shrink_to_fit();
-    invalid_lines.shrink_to_fit();
+    fn finish(mut self) -> (Vec<CodeOwnerRule>, Vec<InvalidLine>) {
+        self.rules.shrink_to_fit();
+        self.invalid_lines.shrink_to_fit();
+        (self.rules, self.invalid_lines)
+    }
+}
 
-    Ok((rules, invalid_lines))
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed_line = line.trim();
+    trimmed_line.is_empty() || trimmed_line.starts_with('#')
 }
 
-// Validate that the pattern can be turned into valid globs
-fn validate_pattern(pattern: &str, original_path: &str) -> Result<(), &'static str> {
-    use globset::Glob;
-
-    let is_anchored = original_path.starts_with('/');
-    let is_directory = original_path.ends_with('/');
-
-    // Test that we can create the necessary globs
-    match (is_anchored, is_directory) {
-        (true, true) => {
-            // /docs/ → need to create "docs" and "docs/**"
-            Glob::new(pattern).map_err(|_| "invalid pattern")?;
-            Glob::new(&format!("{}/**", pattern)).map_err(|_| "invalid pattern")?;
+/// Parses a single line. Returns `None` for blank lines and comments, and
+/// the reason plus column for lines that cannot be used as a rule.
+fn parse_line(
+    line: &str,
+    line_number: usize,
+) -> Option<Result<CodeOwnerRule, (InvalidReason, usize)>> {
+    if is_blank_or_comment(line) {
+        // Skip empty lines and comments
+        return None;
+    }
+
+    let spans = token_spans(line);
+    let pattern_span = spans.first()?.clone();
+    let original_path = &line[pattern_span.clone()];
+    let unescaped = unescape_pattern(original_path);
+    let pattern = unescaped.trim_matches('/');
+
+    // Basic validation - ensure pattern is not empty after trimming
+    if pattern.is_empty() {
+        return Some(Err((InvalidReason::EmptyPattern, pattern_span.start)));
+    }
+
+    if original_path.starts_with('!') {
+        return Some(Err((
+            InvalidReason::UnsupportedNegation,
+            pattern_span.start,
+        )));
+    }
+
+    // Check for invalid glob patterns
+    if let Err(err) = validate_pattern(pattern, original_path) {
+        let glob_error = err.kind().to_string();
+        let reason = match err.kind() {
+            globset::ErrorKind::UnclosedClass => {
+                InvalidReason::UnclosedCharacterClass { glob_error }
+            }
+            _ => InvalidReason::InvalidGlob { glob_error },
+        };
+        let column = match reason {
+            InvalidReason::UnclosedCharacterClass { .. } => original_path
+                .rfind('[')
+                .map_or(pattern_span.start, |idx| pattern_span.start + idx),
+            _ => pattern_span.start,
+        };
+        return Some(Err((reason, column)));
+    }
+
+    Some(Ok(CodeOwnerRule {
+        pattern: pattern.to_string(),
+        owners: spans[1..]
+            .iter()
+            .map(|span| line[span.clone()].to_string())
+            .collect(),
+        original_path: original_path.to_string(),
+        line_number,
+    }))
+}
+
+/// Tokens on a rule line: byte ranges of the pattern and each owner, and
+/// the offset of a trailing comment if there is one.
+pub(crate) struct LineTokens {
+    pub spans: Vec<Range<usize>>,
+    pub comment: Option<usize>,
+}
+
+/// Splits a line on unescaped whitespace. A backslash keeps the following
+/// character in the token, so `docs/My\ Folder/` is one token, and a `#`
+/// starting any token after the pattern opens a comment that runs to the
+/// end of the line.
+pub(crate) fn tokenize(line: &str) -> LineTokens {
+    let mut spans = Vec::new();
+    let mut start = None;
+    let mut escaped = false;
+
+    for (idx, ch) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
         }
-        (true, false) => {
-            // /src/file.rs → need to create "src/file.rs"
-            Glob::new(pattern).map_err(|_| "invalid pattern")?;
+        if ch.is_whitespace() {
+            if let Some(token_start) = start.take() {
+                spans.push(token_start..idx);
+            }
+            continue;
         }
-        (false, true) => {
-            // lib/ → need to create "**/lib" and "**/lib/**"
-            Glob::new(&format!("**/{}", pattern)).map_err(|_| "invalid pattern")?;
-            Glob::new(&format!("**/{}/**", pattern)).map_err(|_| "invalid pattern")?;
+        if start.is_none() {
+            if ch == '#' && !spans.is_empty() {
+                return LineTokens {
+                    spans,
+                    comment: Some(idx),
+                };
+            }
+            start = Some(idx);
         }
-        (false, false) => {
-            // *.rs or file.txt → need to create pattern or "**/pattern"
-            if pattern.contains('*') || pattern.contains('?') || pattern.contains('[') {
-                Glob::new(pattern).map_err(|_| "invalid pattern")?;
-            } else {
-                Glob::new(&format!("**/{}", pattern)).map_err(|_| "invalid pattern")?;
+        escaped = ch == '\\';
+    }
+    if let Some(token_start) = start {
+        spans.push(token_start..line.len());
+    }
+
+    LineTokens {
+        spans,
+        comment: None,
+    }
+}
+
+/// Byte ranges of the pattern and owner tokens on a line.
+pub(crate) fn token_spans(line: &str) -> Vec<Range<usize>> {
+    tokenize(line).spans
+}
+
+/// Resolves the escapes that only exist to get past tokenising (`\ ` and
+/// `\#`). Everything else, like `\*`, is left for globset to interpret.
+fn unescape_pattern(token: &str) -> Cow<'_, str> {
+    if !token.contains('\\') {
+        return Cow::Borrowed(token);
+    }
+
+    let mut unescaped = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next() {
+                Some(next) if next == '#' || next.is_whitespace() => unescaped.push(next),
+                Some(next) => {
+                    unescaped.push(ch);
+                    unescaped.push(next);
+                }
+                None => unescaped.push(ch),
             }
+        } else {
+            unescaped.push(ch);
         }
     }
 
-    Ok(())
+    Cow::Owned(unescaped)
+}
+
+// Validate that the pattern can be turned into valid globs
+fn validate_pattern(pattern: &str, original_path: &str) -> Result<(), globset::Error> {
+    rule_globs(pattern, original_path).map(|_| ())
 }
 
 #[cfg(test)]
@@ -131,6 +458,7 @@ mod tests {
         assert_eq!(rules[0].pattern, "src/lib.rs");
         assert_eq!(rules[0].owners, vec!["@alice"]);
         assert_eq!(rules[0].original_path, "src/lib.rs");
+        assert_eq!(rules[0].line_number, 1);
         assert!(invalids.is_empty());
     }
 
@@ -140,6 +468,7 @@ mod tests {
         let (rules, _) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
         assert_eq!(rules.len(), 1);
         assert_eq!(rules[0].owners, vec!["@bob"]);
+        assert_eq!(rules[0].line_number, 3);
     }
 
     #[test]
@@ -148,6 +477,46 @@ mod tests {
         let (_, invalids) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
         assert_eq!(invalids.len(), 1);
         assert!(invalids[0].content.contains("docs/["));
+        assert!(matches!(
+            &invalids[0].reason,
+            InvalidReason::UnclosedCharacterClass { glob_error } if glob_error.contains("']'")
+        ));
+        assert_eq!(invalids[0].column, 5);
+    }
+
+    #[test]
+    fn reports_column_of_indented_pattern() {
+        let file = with_temp_codeowners("src/   @a\n   !vendor/ @team\n");
+        let (rules, invalids) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(invalids.len(), 1);
+        assert_eq!(invalids[0].line_number, 2);
+        assert_eq!(invalids[0].reason, InvalidReason::UnsupportedNegation);
+        assert_eq!(invalids[0].column, 3);
+        assert_eq!(
+            invalids[0].to_string(),
+            "line 2, column 4: negated patterns are not supported"
+        );
+    }
+
+    #[test]
+    fn keeps_rules_without_owners() {
+        let file = with_temp_codeowners("/docs/\n");
+        let (rules, invalids) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
+        assert!(invalids.is_empty());
+        assert_eq!(rules[0].pattern, "docs");
+        assert!(rules[0].is_unowned());
+    }
+
+    #[test]
+    fn reports_other_glob_errors() {
+        let file = with_temp_codeowners("src/{a,b @team\n");
+        let (_, invalids) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
+        assert!(matches!(
+            invalids[0].reason,
+            InvalidReason::InvalidGlob { .. }
+        ));
+        assert!(invalids[0].reason.glob_error().is_some());
     }
 
     #[test]
@@ -186,11 +555,175 @@ mod tests {
         assert_eq!(rules[1].original_path, "/src/");
     }
 
+    #[test]
+    fn reports_non_utf8_lines() {
+        let file = with_temp_codeowners("");
+        write(file.path(), b"/src/ @a\n/caf\xE9/ @b\n# r\xE9sum\xE9\n").unwrap();
+        let (rules, invalids) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(invalids.len(), 1);
+        assert_eq!(invalids[0].line_number, 2);
+        assert_eq!(
+            invalids[0].reason,
+            InvalidReason::NonUtf8 { byte_offset: 4 }
+        );
+        assert_eq!(invalids[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn non_utf8_as_warning_keeps_rule() {
+        let file = with_temp_codeowners("");
+        write(file.path(), b"/caf\xE9/ @b\n").unwrap();
+        let options = ParseOptions {
+            non_utf8: Severity::Warning,
+            ..ParseOptions::default()
+        };
+        let (rules, invalids) =
+            parse_codeowners_file_with_options(file.path().to_str().unwrap(), &options).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].pattern, "caf\u{FFFD}");
+        assert_eq!(invalids[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn strips_and_reports_bom() {
+        let file = with_temp_codeowners("\u{FEFF}/src/ @a\n");
+        let (rules, invalids) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(rules[0].original_path, "/src/");
+        assert_eq!(invalids.len(), 1);
+        assert_eq!(invalids[0].reason, InvalidReason::ByteOrderMark);
+        assert_eq!(invalids[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn detects_crlf_once() {
+        let file = with_temp_codeowners("# owners\r\n/src/ @a\r\n/docs/ @b\r\n");
+        let options = ParseOptions {
+            crlf: Severity::Error,
+            ..ParseOptions::default()
+        };
+        let (rules, invalids) =
+            parse_codeowners_file_with_options(file.path().to_str().unwrap(), &options).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].owners, vec!["@b"]);
+        assert_eq!(invalids.len(), 1);
+        assert_eq!(invalids[0].line_number, 1);
+        assert_eq!(invalids[0].reason, InvalidReason::CrlfLineEnding);
+        assert_eq!(invalids[0].column, 8);
+        assert_eq!(invalids[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn parse_str_matches_file_parsing() {
+        let content = "# comment\n/src/ @alice\ndocs/[ @bad\n";
+        let file = with_temp_codeowners(content);
+        let from_file = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
+        let from_str = parse_str(content);
+        assert_eq!(from_str.0, from_file.0);
+        assert_eq!(from_str.1, from_file.1);
+        assert_eq!(from_str.0[0].line_number, 2);
+    }
+
+    #[test]
+    fn parse_bytes_reports_non_utf8() {
+        let (rules, invalids) = parse_bytes(b"/caf\xE9/ @b\n/src/ @a");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].line_number, 2);
+        assert_eq!(
+            invalids[0].reason,
+            InvalidReason::NonUtf8 { byte_offset: 4 }
+        );
+    }
+
+    #[test]
+    fn parse_reader_accepts_any_bufread() {
+        let reader = io::Cursor::new(b"*.rs @rust\n".to_vec());
+        let (rules, invalids) = parse_reader(reader).unwrap();
+        assert_eq!(rules[0].pattern, "*.rs");
+        assert!(invalids.is_empty());
+    }
+
+    #[test]
+    fn memory_and_reader_agree() {
+        let content = b"\xEF\xBB\xBF/src/ @a\r\n/caf\xE9/ @b\n# end\n*.rs";
+        assert_eq!(
+            parse_bytes(content),
+            parse_reader(io::Cursor::new(content)).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_str_with_options_applies_severity() {
+        let options = ParseOptions {
+            crlf: Severity::Error,
+            ..ParseOptions::default()
+        };
+        let (_, invalids) = parse_str_with_options("/src/ @a\r\n", &options);
+        assert_eq!(invalids[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn keeps_escaped_spaces_in_pattern() {
+        let (rules, invalids) = parse_str("docs/My\\ Folder/ @team\n");
+        assert!(invalids.is_empty());
+        assert_eq!(rules[0].pattern, "docs/My Folder");
+        assert_eq!(rules[0].original_path, "docs/My\\ Folder/");
+        assert_eq!(rules[0].owners, vec!["@team"]);
+    }
+
+    #[test]
+    fn escaped_hash_starts_a_pattern() {
+        let (rules, _) = parse_str("\\#file @a\n/notes/\\#1.md @b\n");
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].pattern, "#file");
+        assert_eq!(rules[1].pattern, "notes/#1.md");
+    }
+
+    #[test]
+    fn strips_trailing_comments() {
+        let (rules, _) = parse_str("/src/ @a @b # platform team\n/lib/ @c#d\n");
+        assert_eq!(rules[0].owners, vec!["@a", "@b"]);
+        // `#` inside a token is not a comment
+        assert_eq!(rules[1].owners, vec!["@c#d"]);
+    }
+
+    #[test]
+    fn comment_right_after_pattern_means_no_owners() {
+        let (rules, invalids) = parse_str("/docs/ # nobody\n");
+        assert!(invalids.is_empty());
+        assert!(rules[0].is_unowned());
+    }
+
+    #[test]
+    fn other_escapes_are_left_for_globset() {
+        let (rules, invalids) = parse_str("/src/\\*.rs @a\n");
+        assert!(invalids.is_empty());
+        assert_eq!(rules[0].pattern, "src/\\*.rs");
+    }
+
+    #[test]
+    fn tokenize_reports_comment_offset() {
+        let tokens = tokenize("a\\ b  @x # c");
+        assert_eq!(tokens.spans, vec![0..4, 6..8]);
+        assert_eq!(tokens.comment, Some(9));
+    }
+
+    #[test]
+    fn token_spans_skip_alignment_whitespace() {
+        let line = "  /src/   @alice\t@bob ";
+        let spans = token_spans(line);
+        assert_eq!(spans.len(), 3);
+        assert_eq!(&line[spans[0].clone()], "/src/");
+        assert_eq!(&line[spans[1].clone()], "@alice");
+        assert_eq!(&line[spans[2].clone()], "@bob");
+    }
+
     #[test]
     fn rejects_empty_pattern() {
         let file = with_temp_codeowners("/ @team\n");
         let (rules, invalids) = parse_codeowners_file(file.path().to_str().unwrap()).unwrap();
         assert_eq!(rules.len(), 0);
         assert_eq!(invalids.len(), 1);
+        assert_eq!(invalids[0].reason, InvalidReason::EmptyPattern);
     }
 }
@@ -0,0 +1,286 @@
+use crate::diagnostic::{Diagnostic, Highlight, Label, Severity};
+use crate::parser::token_spans;
+use std::fmt::Write;
+use std::ops::Range;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+
+/// Renders diagnostics in the style of rustc, with the offending CODEOWNERS
+/// lines quoted and underlined.
+pub struct Renderer<'a> {
+    path: &'a str,
+    lines: Vec<&'a str>,
+    color: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(path: &'a str, source: &'a str, color: bool) -> Self {
+        Renderer {
+            path,
+            lines: source.lines().collect(),
+            color,
+        }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+
+        let (level, level_style) = match diagnostic.severity {
+            Severity::Error => ("error", RED),
+            Severity::Warning => ("warning", YELLOW),
+        };
+        let _ = writeln!(
+            out,
+            "{}{}",
+            self.paint(&format!("{}[{}]", level, diagnostic.code), level_style),
+            self.paint(&format!(": {}", diagnostic.message), BOLD)
+        );
+
+        // Primary label and related notes share one snippet, in file order
+        let mut labels: Vec<(&Label, bool)> = Vec::new();
+        if let Some(primary) = &diagnostic.primary {
+            labels.push((primary, true));
+        }
+        labels.extend(diagnostic.notes.iter().map(|note| (note, false)));
+        labels.retain(|(label, _)| self.line(label.line_number).is_some());
+
+        let width = labels
+            .iter()
+            .map(|(label, _)| label.line_number.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(width);
+        let pipe = self.paint("|", BLUE);
+
+        match diagnostic
+            .primary
+            .as_ref()
+            .filter(|label| self.line(label.line_number).is_some())
+        {
+            Some(primary) => {
+                let column = self.span(primary).start + 1;
+                let _ = writeln!(
+                    out,
+                    "{}{} {}:{}:{}",
+                    gutter,
+                    self.paint("-->", BLUE),
+                    self.path,
+                    primary.line_number,
+                    column
+                );
+            }
+            None => {
+                let _ = writeln!(out, "{}{} {}", gutter, self.paint("-->", BLUE), self.path);
+            }
+        }
+
+        if !labels.is_empty() {
+            labels.sort_by_key(|(label, _)| label.line_number);
+            let _ = writeln!(out, "{} {}", gutter, pipe);
+
+            let mut previous_line = None;
+            for (label, is_primary) in &labels {
+                if let Some(previous) = previous_line {
+                    if label.line_number > previous + 1 {
+                        let _ = writeln!(out, "{}", self.paint("...", BLUE));
+                    }
+                }
+                previous_line = Some(label.line_number);
+
+                let text = self.line(label.line_number).unwrap_or_default();
+                let span = self.span(label);
+                let (marker, style) = if *is_primary {
+                    ('^', level_style)
+                } else {
+                    ('-', BLUE)
+                };
+
+                let _ = writeln!(
+                    out,
+                    "{} {} {}",
+                    self.paint(&format!("{:>width$}", label.line_number), BLUE),
+                    pipe,
+                    text
+                );
+                let padding = " ".repeat(display_width(&text[..span.start]));
+                let underline = marker
+                    .to_string()
+                    .repeat(display_width(&text[span.clone()]).max(1));
+                let _ = writeln!(
+                    out,
+                    "{} {} {}{}",
+                    gutter,
+                    pipe,
+                    padding,
+                    self.paint(format!("{} {}", underline, label.message).trim_end(), style)
+                );
+            }
+        }
+
+        if !labels.is_empty() && !diagnostic.help.is_empty() {
+            let _ = writeln!(out, "{} {}", gutter, pipe);
+        }
+        for help in &diagnostic.help {
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                gutter,
+                self.paint("=", BLUE),
+                format_args!("{}: {}", self.paint("help", BOLD), help)
+            );
+        }
+
+        if let Some(suggestion) = &diagnostic.suggestion {
+            let applied = diagnostic.primary.as_ref().and_then(|primary| {
+                let text = self.line(primary.line_number)?;
+                let span = self.span(primary);
+                Some((primary.line_number, text, span))
+            });
+
+            match applied {
+                Some((line_number, text, span)) if !suggestion.replacement.is_empty() => {
+                    let _ = writeln!(out, "{}: {}", self.paint("help", CYAN), suggestion.message);
+                    let fixed = format!(
+                        "{}{}{}",
+                        &text[..span.start],
+                        suggestion.replacement,
+                        &text[span.end..]
+                    );
+                    let padding = " ".repeat(display_width(&text[..span.start]));
+                    let underline = "~".repeat(display_width(&suggestion.replacement).max(1));
+                    let _ = writeln!(out, "{} {}", gutter, pipe);
+                    let _ = writeln!(
+                        out,
+                        "{} {} {}",
+                        self.paint(&format!("{:>width$}", line_number), BLUE),
+                        pipe,
+                        fixed
+                    );
+                    let _ = writeln!(
+                        out,
+                        "{} {} {}{}",
+                        gutter,
+                        pipe,
+                        padding,
+                        self.paint(&underline, CYAN)
+                    );
+                }
+                _ => {
+                    let _ = writeln!(out, "{}: {}", self.paint("help", CYAN), suggestion.message);
+                }
+            }
+        }
+
+        out
+    }
+
+    fn line(&self, line_number: usize) -> Option<&'a str> {
+        line_number
+            .checked_sub(1)
+            .and_then(|idx| self.lines.get(idx))
+            .copied()
+    }
+
+    /// Byte range of the highlighted text within the label's line.
+    fn span(&self, label: &Label) -> Range<usize> {
+        let text = self.line(label.line_number).unwrap_or_default();
+        let trimmed_start = text.len() - text.trim_start().len();
+        let line_span = trimmed_start..text.trim_end().len().max(trimmed_start);
+
+        let span = match &label.highlight {
+            Highlight::Line => Some(line_span.clone()),
+            Highlight::Pattern => token_spans(text).into_iter().next(),
+            Highlight::Owner(idx) => token_spans(text).into_iter().nth(idx + 1),
+            Highlight::Columns(columns) => Some(columns.clone()),
+        };
+
+        span.filter(|span| {
+            span.start <= span.end
+                && span.end <= text.len()
+                && text.is_char_boundary(span.start)
+                && text.is_char_boundary(span.end)
+        })
+        .unwrap_or(line_span)
+    }
+
+    fn paint(&self, text: &str, style: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(|ch| if ch == '\t' { 4 } else { 1 }).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::Applicability;
+
+    const SOURCE: &str = "# owners\n/docs/ @a\n\n*.md   @docs @b\n";
+
+    #[test]
+    fn underlines_pattern() {
+        let diagnostic = Diagnostic::error("exists", "missing").with_primary(
+            2,
+            Highlight::Pattern,
+            "nothing here",
+        );
+        let output = Renderer::new("CODEOWNERS", SOURCE, false).render(&diagnostic);
+        assert_eq!(
+            output,
+            "error[exists]: missing\n\
+             \x20--> CODEOWNERS:2:1\n\
+             \x20 |\n\
+             2 | /docs/ @a\n\
+             \x20 | ^^^^^^ nothing here\n"
+        );
+    }
+
+    #[test]
+    fn underlines_owner_and_related_note() {
+        let diagnostic = Diagnostic::warning("owners", "bad owner")
+            .with_primary(4, Highlight::Owner(1), "here")
+            .with_note(2, Highlight::Pattern, "see also");
+        let output = Renderer::new("CODEOWNERS", SOURCE, false).render(&diagnostic);
+        assert!(output.starts_with("warning[owners]: bad owner\n --> CODEOWNERS:4:14\n"));
+        assert!(output.contains("2 | /docs/ @a\n  | ------ see also\n...\n"));
+        assert!(output.contains("4 | *.md   @docs @b\n  |              ^^ here\n"));
+    }
+
+    #[test]
+    fn renders_help_and_suggestion() {
+        let diagnostic = Diagnostic::error("style", "needs slash")
+            .with_primary(2, Highlight::Pattern, "")
+            .with_help("directories should end in `/`")
+            .with_suggestion("use", "/docs/**", Applicability::MaybeIncorrect);
+        let output = Renderer::new("CODEOWNERS", SOURCE, false).render(&diagnostic);
+        assert!(output.contains("  = help: directories should end in `/`\n"));
+        assert!(output.contains("help: use\n  |\n2 | /docs/** @a\n  | ~~~~~~~~\n"));
+    }
+
+    #[test]
+    fn file_level_diagnostic_has_no_snippet() {
+        let diagnostic = Diagnostic::warning("limits", "file is large");
+        let output = Renderer::new("CODEOWNERS", SOURCE, false).render(&diagnostic);
+        assert_eq!(output, "warning[limits]: file is large\n --> CODEOWNERS\n");
+    }
+
+    #[test]
+    fn colors_only_when_enabled() {
+        let diagnostic = Diagnostic::error("exists", "missing");
+        let plain = Renderer::new("CODEOWNERS", SOURCE, false).render(&diagnostic);
+        let colored = Renderer::new("CODEOWNERS", SOURCE, true).render(&diagnostic);
+        assert!(!plain.contains('\x1b'));
+        assert!(colored.contains(RED));
+    }
+}
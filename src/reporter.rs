@@ -0,0 +1,25 @@
+use crate::diagnostic::Diagnostic;
+use std::error::Error;
+use std::time::Duration;
+
+/// Receives progress events while validation runs.
+///
+/// The library never prints; embedders decide what to do with each event.
+/// Every method has an empty default so implementations only override what
+/// they care about.
+pub trait Reporter: Sync {
+    fn validator_started(&self, _name: &str) {}
+
+    fn validator_finished(&self, _name: &str, _elapsed: Duration, _issues: usize) {}
+
+    fn diagnostic(&self, _diagnostic: &Diagnostic) {}
+
+    /// A problem that did not stop validation, e.g. an unreadable directory.
+    fn error(&self, _validator: &str, _error: &dyn Error) {}
+}
+
+/// Discards every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopReporter;
+
+impl Reporter for NoopReporter {}
@@ -0,0 +1,84 @@
+use crate::discovery::Forge;
+use crate::error::Result;
+use crate::file_index::FileIndex;
+use crate::files::{FileOptions, FileSource};
+use crate::parser::CodeOwnerRule;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Everything a validator may look at besides its own arguments.
+#[derive(Debug, Clone)]
+pub struct ValidationContext<'a> {
+    pub rules: &'a [CodeOwnerRule],
+    /// The CODEOWNERS file exactly as read, before decoding. Empty when the
+    /// rules did not come from a file.
+    pub source: &'a [u8],
+    pub repo_root: &'a Path,
+    pub forge: Forge,
+    /// Where `exists`, `coverage` and other repository checks get files from
+    pub file_source: FileSource<'a>,
+    pub file_options: &'a FileOptions,
+    /// The repository's files, listed the first time a validator asks
+    files: OnceLock<FileIndex>,
+}
+
+impl<'a> ValidationContext<'a> {
+    /// A context for `rules` in the current directory, without source bytes.
+    pub fn new(rules: &'a [CodeOwnerRule]) -> Self {
+        ValidationContext {
+            rules,
+            source: &[],
+            repo_root: Path::new("."),
+            forge: Forge::default(),
+            file_source: FileSource::default(),
+            file_options: FileOptions::DEFAULT,
+            files: OnceLock::new(),
+        }
+    }
+
+    pub fn with_source(mut self, source: &'a [u8]) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_repo_root(mut self, repo_root: &'a Path) -> Self {
+        self.repo_root = repo_root;
+        self
+    }
+
+    pub fn with_forge(mut self, forge: Forge) -> Self {
+        self.forge = forge;
+        self
+    }
+
+    pub fn with_file_source(mut self, file_source: FileSource<'a>) -> Self {
+        self.file_source = file_source;
+        self
+    }
+
+    pub fn with_file_options(mut self, file_options: &'a FileOptions) -> Self {
+        self.file_options = file_options;
+        self
+    }
+
+    /// Uses an already built index instead of listing `file_source`.
+    pub fn with_files(mut self, files: FileIndex) -> Self {
+        self.files = OnceLock::from(files);
+        self
+    }
+
+    /// Whether the files are already listed.
+    pub fn has_files(&self) -> bool {
+        self.files.get().is_some()
+    }
+
+    /// The files from `file_source` with `file_options` applied. They are
+    /// listed once and shared by every validator that looks at them.
+    pub fn files(&self) -> Result<&FileIndex> {
+        if let Some(files) = self.files.get() {
+            return Ok(files);
+        }
+        let files = FileIndex::build(self.repo_root, self.file_source, self.file_options)?;
+        Ok(self.files.get_or_init(|| files))
+    }
+}
@@ -0,0 +1,126 @@
+use crate::error::Result;
+use crate::file_index::FileIndex;
+use crate::ownership::{Ownership, OwnershipResolver};
+use crate::parser::CodeOwnerRule;
+use std::path::PathBuf;
+
+/// How many files in the repository have owners.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub files: usize,
+    /// Files no rule matches
+    pub unowned: Vec<PathBuf>,
+    /// Files whose last matching rule has no owners. These are intentional
+    /// and do not count as gaps.
+    pub explicitly_unowned: Vec<PathBuf>,
+}
+
+impl Coverage {
+    pub fn owned(&self) -> usize {
+        self.files - self.unowned.len() - self.explicitly_unowned.len()
+    }
+}
+
+pub fn validate_coverage(rules: &[CodeOwnerRule], files: &FileIndex) -> Result<Coverage> {
+    let resolver = OwnershipResolver::new(rules)?;
+    let files = files.ownable_paths();
+
+    let mut coverage = Coverage {
+        files: files.len(),
+        ..Coverage::default()
+    };
+    for file in files {
+        match resolver.resolve(&file) {
+            Ownership::Owned(_) => {}
+            Ownership::ExplicitlyUnowned(_) => coverage.explicitly_unowned.push(file),
+            Ownership::Unowned => coverage.unowned.push(file),
+        }
+    }
+
+    Ok(coverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{FileOptions, FileSource};
+    use crate::parser::parse_str;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn worktree(repo: &Path, options: &FileOptions) -> FileIndex {
+        FileIndex::build(repo, FileSource::Worktree, options).unwrap()
+    }
+
+    #[test]
+    fn separates_unowned_from_explicitly_unowned() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("src/generated")).unwrap();
+        fs::write(tmp.path().join("src/lib.rs"), "").unwrap();
+        fs::write(tmp.path().join("src/generated/api.rs"), "").unwrap();
+        fs::write(tmp.path().join("README.md"), "").unwrap();
+
+        let (rules, _) = parse_str("/src/ @core\n/src/generated/\n");
+        let coverage =
+            validate_coverage(&rules, &worktree(tmp.path(), FileOptions::DEFAULT)).unwrap();
+
+        assert_eq!(coverage.files, 3);
+        assert_eq!(coverage.owned(), 1);
+        assert_eq!(coverage.unowned, vec![PathBuf::from("README.md")]);
+        assert_eq!(
+            coverage.explicitly_unowned,
+            vec![PathBuf::from("src/generated/api.rs")]
+        );
+    }
+
+    #[test]
+    fn directory_names_cover_their_contents() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("config")).unwrap();
+        fs::write(tmp.path().join("config/app.toml"), "").unwrap();
+
+        let (rules, _) = parse_str("/config @cfg\n");
+        let coverage =
+            validate_coverage(&rules, &worktree(tmp.path(), FileOptions::DEFAULT)).unwrap();
+        assert_eq!(coverage.files, 1);
+        assert!(coverage.unowned.is_empty());
+    }
+
+    #[test]
+    fn ignores_the_git_directory() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir(tmp.path().join(".git")).unwrap();
+        fs::write(tmp.path().join(".git/HEAD"), "").unwrap();
+        fs::write(tmp.path().join("main.rs"), "").unwrap();
+
+        let (rules, _) = parse_str("*.rs @core\n");
+        let coverage =
+            validate_coverage(&rules, &worktree(tmp.path(), FileOptions::DEFAULT)).unwrap();
+        assert_eq!(coverage.files, 1);
+        assert!(coverage.unowned.is_empty());
+    }
+
+    #[test]
+    fn counts_submodules_but_not_their_files() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("vendor/lib/src")).unwrap();
+        fs::write(
+            tmp.path().join(".gitmodules"),
+            "[submodule \"lib\"]\n\tpath = vendor/lib\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("vendor/lib/src/lib.rs"), "").unwrap();
+
+        let (rules, _) = parse_str(".gitmodules @core\n");
+        let options = FileOptions {
+            descend_submodules: true,
+            ..FileOptions::default()
+        };
+        for options in [FileOptions::DEFAULT, &options] {
+            let coverage = validate_coverage(&rules, &worktree(tmp.path(), options)).unwrap();
+            assert_eq!(coverage.files, 2);
+            assert_eq!(coverage.unowned, vec![PathBuf::from("vendor/lib")]);
+        }
+    }
+}
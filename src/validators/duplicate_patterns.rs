@@ -16,13 +16,6 @@ pub fn validate_duplicates(rules: &[CodeOwnerRule]) -> Vec<CodeOwnerRule> {
 
         if is_original_path_duplicate || is_pattern_duplicate {
             duplicates.push(rule.clone());
-            // Only print a warning if it is the normalized duplicate.
-            if !is_original_path_duplicate && is_pattern_duplicate {
-                println!("Warning: Duplicate pattern found in normalized pattern");
-                println!("Please raise an issue if this seems incorrect.");
-                println!("Pattern: {}", &rule.pattern);
-                println!("Original: {}", &rule.original_path);
-            }
         }
     }
 
@@ -39,6 +32,7 @@ mod tests {
             pattern: pattern.trim_matches('/').to_string(),
             original_path: original.to_string(),
             owners: vec!["@owner".to_string()],
+            line_number: 0,
         }
     }
 
@@ -1,77 +1,218 @@
-use crate::parser::CodeOwnerRule;
-use globset::{Glob, GlobSetBuilder};
-use ignore::{DirEntry, WalkBuilder, WalkState};
-use rustc_hash::FxHashMap;
-use std::error::Error;
-use std::path::Path;
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use crate::error::Result;
+use crate::file_index::FileIndex;
+use crate::files::{
+    containing_submodule, walk_threads, walk_worktree, worktree_submodules, EntryKind, Excludes,
+    FileOptions, FileSource, SymlinkPolicy,
 };
+use crate::matcher::{MatchKind, RuleMatcher};
+use crate::parser::CodeOwnerRule;
+use crate::reporter::{NoopReporter, Reporter};
+use ignore::WalkState;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+// What a rule has matched so far
+const SEEN_FILE: u8 = 1;
+const SEEN_DIRECTORY: u8 = 2;
+
+/// Why a rule's pattern does not line up with the repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryIssue {
+    /// Nothing matches the pattern
+    Missing,
+    /// A directory pattern (trailing `/`) only matches files
+    NotADirectory,
+    /// An anchored pattern without a trailing `/` names a directory, so
+    /// ownership of its contents relies on implicit semantics
+    ImplicitDirectory,
+    /// The pattern names paths inside a submodule, where the forge applies
+    /// the submodule's own CODEOWNERS instead
+    InsideSubmodule { submodule: PathBuf },
+}
 
-fn build_globset_with_mapping(
-    rules: &[&CodeOwnerRule],
-) -> Result<(globset::GlobSet, FxHashMap<usize, usize>), globset::Error> {
-    let mut builder = GlobSetBuilder::new();
-    let mut globset_idx_to_rule_idx = FxHashMap::default();
-    let mut globset_idx = 0;
-
-    for (rule_idx, rule) in rules.iter().enumerate() {
-        let pattern = &rule.pattern;
-        let is_directory = rule.original_path.ends_with('/');
-        let is_anchored = rule.original_path.starts_with('/');
-
-        match (is_anchored, is_directory) {
-            (true, true) => {
-                // /docs/ → match "docs" and "docs/**"
-                builder.add(Glob::new(pattern)?);
-                globset_idx_to_rule_idx.insert(globset_idx, rule_idx);
-                globset_idx += 1;
-
-                builder.add(Glob::new(&format!("{}/**", pattern))?);
-                globset_idx_to_rule_idx.insert(globset_idx, rule_idx);
-                globset_idx += 1;
-            }
-            (true, false) => {
-                // /src/file.rs → match "src/file.rs" exactly
-                builder.add(Glob::new(pattern)?);
-                globset_idx_to_rule_idx.insert(globset_idx, rule_idx);
-                globset_idx += 1;
-            }
-            (false, true) => {
-                // lib/ → match "**/lib" and "**/lib/**"
-                builder.add(Glob::new(&format!("**/{}", pattern))?);
-                globset_idx_to_rule_idx.insert(globset_idx, rule_idx);
-                globset_idx += 1;
-
-                builder.add(Glob::new(&format!("**/{}/**", pattern))?);
-                globset_idx_to_rule_idx.insert(globset_idx, rule_idx);
-                globset_idx += 1;
-            }
-            (false, false) => {
-                // *.rs → match "**/*.rs" (or just pattern if it's already a glob)
-                if pattern.contains('*') || pattern.contains('?') || pattern.contains('[') {
-                    // Already a wildcard pattern like *.rs, **/*.md
-                    builder.add(Glob::new(pattern)?);
-                    globset_idx_to_rule_idx.insert(globset_idx, rule_idx);
-                    globset_idx += 1;
-                } else {
-                    // Plain file like config.json → match "**/config.json"
-                    builder.add(Glob::new(&format!("**/{}", pattern))?);
-                    globset_idx_to_rule_idx.insert(globset_idx, rule_idx);
-                    globset_idx += 1;
-                }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryFinding {
+    pub rule: CodeOwnerRule,
+    pub issue: EntryIssue,
+}
+
+/// How many files one rule matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleStats {
+    pub rule: CodeOwnerRule,
+    /// Files the pattern matches
+    pub matched: usize,
+    /// Files whose owners the rule decides, being the last rule to match
+    pub effective: usize,
+    /// The first matched files, up to the number asked for
+    pub examples: Vec<PathBuf>,
+}
+
+/// Match counts for every rule, in the order of the rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchStats {
+    /// Files in the repository, counting each submodule as one
+    pub files: usize,
+    pub rules: Vec<RuleStats>,
+}
+
+impl MatchStats {
+    /// The fraction of the repository's files a rule matches.
+    pub fn share(&self, stats: &RuleStats) -> f64 {
+        if self.files == 0 {
+            return 0.0;
+        }
+        stats.matched as f64 / self.files as f64
+    }
+}
+
+/// Counts the files each rule matches and owns, keeping up to `examples`
+/// matched paths per rule.
+pub fn rule_stats(
+    rules: &[CodeOwnerRule],
+    files: &FileIndex,
+    examples: usize,
+) -> Result<MatchStats> {
+    let matcher = RuleMatcher::new(rules)?;
+    let files = files.ownable_paths();
+
+    let mut stats: Vec<RuleStats> = rules
+        .iter()
+        .map(|rule| RuleStats {
+            rule: rule.clone(),
+            matched: 0,
+            effective: 0,
+            examples: Vec::new(),
+        })
+        .collect();
+    for file in &files {
+        let matches = matcher.matches(file);
+        for &idx in &matches {
+            let rule = &mut stats[idx];
+            rule.matched += 1;
+            if rule.examples.len() < examples {
+                rule.examples.push(file.clone());
             }
         }
+        if let Some(&last) = matches.last() {
+            stats[last].effective += 1;
+        }
+    }
+
+    Ok(MatchStats {
+        files: files.len(),
+        rules: stats,
+    })
+}
+
+/// Returns the rules that match nothing in the repository.
+pub fn validate_directory(repo_path: &Path, rules: &[CodeOwnerRule]) -> Result<Vec<CodeOwnerRule>> {
+    Ok(validate_entries(repo_path, rules)?
+        .into_iter()
+        .filter(|finding| finding.issue == EntryIssue::Missing)
+        .map(|finding| finding.rule)
+        .collect())
+}
+
+/// Like `validate_entries_with`, but against the files from `source`. Only
+/// the working tree is walked; other sources, and a working tree filtered
+/// by `.gitignore`, are listed up front.
+pub fn validate_entries_from(
+    repo_path: &Path,
+    rules: &[CodeOwnerRule],
+    source: FileSource<'_>,
+    options: &FileOptions,
+    reporter: &dyn Reporter,
+) -> Result<Vec<EntryFinding>> {
+    if source == FileSource::Worktree && !options.exclude_ignored {
+        return validate_entries_with(repo_path, rules, options, reporter);
+    }
+    check_entries(&FileIndex::build(repo_path, source, options)?, rules)
+}
+
+/// Checks rules against an already listed index, with the same findings as
+/// `validate_entries`.
+pub fn check_entries(files: &FileIndex, rules: &[CodeOwnerRule]) -> Result<Vec<EntryFinding>> {
+    let matcher = RuleMatcher::new(rules)?;
+    let submodules = files.submodules();
+    let seen: Vec<AtomicU8> = rules.iter().map(|_| AtomicU8::new(0)).collect();
+    // Rules inside a submodule are decided without looking at the files
+    let settled_by: Vec<u8> = rules
+        .iter()
+        .map(|rule| match reached_submodule(rule, &submodules) {
+            Some(_) => 0,
+            None => settled_by(rule),
+        })
+        .collect();
+    let pending = PendingRules::new(&matcher, rules.len());
+    for (rule_idx, _) in settled_by.iter().enumerate().filter(|(_, &by)| by == 0) {
+        pending.settle(rule_idx);
+    }
+
+    for entry in files.entries() {
+        // OPTIMIZATION: Early exit once every rule is settled
+        if pending.is_empty() {
+            break;
+        }
+        // OPTIMIZATION: Skip entries no unsettled rule can match
+        if !pending.reaches(&entry.path) {
+            continue;
+        }
+        let is_dir = entry.kind != EntryKind::File;
+        record_matches(&matcher, &seen, &settled_by, &pending, &entry.path, is_dir);
     }
 
-    Ok((builder.build()?, globset_idx_to_rule_idx))
+    let findings = rules
+        .iter()
+        .zip(seen.iter().map(|flags| flags.load(Ordering::Relaxed)))
+        .filter_map(|(rule, flags)| {
+            let issue = if let Some(submodule) = reached_submodule(rule, &submodules) {
+                EntryIssue::InsideSubmodule {
+                    submodule: submodule.to_path_buf(),
+                }
+            } else if flags == 0 {
+                EntryIssue::Missing
+            } else if is_directory_rule(rule) {
+                if flags & SEEN_DIRECTORY != 0 {
+                    return None;
+                }
+                EntryIssue::NotADirectory
+            } else if is_direct_rule(rule) && flags == SEEN_DIRECTORY {
+                EntryIssue::ImplicitDirectory
+            } else {
+                return None;
+            };
+            Some(EntryFinding {
+                rule: rule.clone(),
+                issue,
+            })
+        })
+        .collect();
+    Ok(findings)
+}
+
+/// Checks that every rule matches something in the working tree, and that
+/// directory patterns match directories.
+pub fn validate_entries(repo_path: &Path, rules: &[CodeOwnerRule]) -> Result<Vec<EntryFinding>> {
+    validate_entries_with(repo_path, rules, FileOptions::DEFAULT, &NoopReporter)
 }
 
-pub fn validate_directory(
+/// Like `validate_entries`, with the excludes and symlink policy from
+/// `options`. `exclude_ignored` is left to `validate_entries_from`. An
+/// unreadable directory fails the check unless every rule matched anyway,
+/// in which case it goes to `reporter`.
+pub fn validate_entries_with(
     repo_path: &Path,
     rules: &[CodeOwnerRule],
-) -> Result<Vec<CodeOwnerRule>, Box<dyn Error>> {
+    options: &FileOptions,
+    reporter: &dyn Reporter,
+) -> Result<Vec<EntryFinding>> {
+    let excludes = Excludes::new(&options.exclude)?;
+    let mut submodules = worktree_submodules(repo_path)?;
+    submodules.retain(|path| !excludes.contains(path));
+
     // OPTIMIZATION: Pre-allocate with estimated capacity
     let estimated_direct = rules.len() / 3;
     let estimated_wildcard = rules.len() - estimated_direct;
@@ -80,122 +221,290 @@ pub fn validate_directory(
     let mut wildcard_rules = Vec::with_capacity(estimated_wildcard);
 
     // Separate direct and wildcard rules
+    let mut findings = Vec::new();
     for rule in rules {
-        if rule.pattern.contains('*')
-            || rule.pattern.contains('?')
-            || rule.pattern.contains('[')
-            || rule.pattern.contains(']')
-        {
-            wildcard_rules.push(rule);
+        if let Some(submodule) = reached_submodule(rule, &submodules) {
+            findings.push(EntryFinding {
+                rule: rule.clone(),
+                issue: EntryIssue::InsideSubmodule {
+                    submodule: submodule.to_path_buf(),
+                },
+            });
+        } else if is_direct_rule(rule) {
+            direct_rules.push(rule);
         } else {
-            // Direct paths - but we need to check if they're anchored
-            // Anchored paths are direct checks, non-anchored need glob matching
-            if rule.original_path.starts_with('/') {
-                direct_rules.push(rule);
-            } else {
-                // Non-anchored non-wildcard patterns like "main.rs" need glob matching
-                wildcard_rules.push(rule);
-            }
+            wildcard_rules.push(rule);
         }
     }
 
     // Check direct paths (fast path for anchored patterns only)
-    let mut missing = Vec::new();
     for rule in direct_rules {
         let path = repo_path.join(&rule.pattern);
-
-        if !path.exists() {
-            missing.push(rule.clone());
+        let is_dir = if excludes.contains(Path::new(&rule.pattern)) {
+            None
+        } else if submodules.contains(Path::new(&rule.pattern)) {
+            // A submodule exists whether or not it is checked out
+            Some(true)
+        } else {
+            // A symlink is a file to the forge, even if it points at a
+            // directory or nowhere
+            match options.symlinks {
+                SymlinkPolicy::Entry => path.symlink_metadata().ok(),
+                SymlinkPolicy::Follow => path.metadata().ok(),
+                SymlinkPolicy::Skip => path
+                    .symlink_metadata()
+                    .ok()
+                    .filter(|meta| !meta.file_type().is_symlink()),
+            }
+            .map(|meta| meta.is_dir())
+        };
+
+        let issue = match is_dir {
+            None => Some(EntryIssue::Missing),
+            Some(is_dir) => match (is_directory_rule(rule), is_dir) {
+                (true, false) => Some(EntryIssue::NotADirectory),
+                (false, true) => Some(EntryIssue::ImplicitDirectory),
+                _ => None,
+            },
+        };
+        if let Some(issue) = issue {
+            findings.push(EntryFinding {
+                rule: rule.clone(),
+                issue,
+            });
         }
     }
 
     if wildcard_rules.is_empty() {
-        return Ok(missing);
+        return Ok(findings);
     }
 
-    let (globset, idx_mapping) = build_globset_with_mapping(&wildcard_rules)?;
+    let matcher = RuleMatcher::new(wildcard_rules.iter().copied())?;
     let num_wildcards = wildcard_rules.len();
 
     // OPTIMIZATION: Use atomic array for lock-free tracking
-    let matched: Arc<Vec<AtomicUsize>> =
-        Arc::new((0..num_wildcards).map(|_| AtomicUsize::new(0)).collect());
-    let remaining = Arc::new(AtomicUsize::new(num_wildcards));
+    let seen: Vec<AtomicU8> = (0..num_wildcards).map(|_| AtomicU8::new(0)).collect();
+    let settled_by: Vec<u8> = wildcard_rules.iter().map(|rule| settled_by(rule)).collect();
+    let pending = PendingRules::new(&matcher, num_wildcards);
 
     // OPTIMIZATION: Dynamic thread count based on workload
-    let thread_count = if num_wildcards > 5000 {
+    let thread_count = walk_threads(if num_wildcards > 5000 {
         num_cpus::get().min(8) // More threads for large workloads
     } else if num_wildcards > 1000 {
         num_cpus::get().min(4) // Moderate threads
     } else {
         2 // Minimal threads for small workloads
-    };
-
-    WalkBuilder::new(repo_path)
-        .standard_filters(false)
-        .hidden(false) // Check hidden files too
-        .git_ignore(false) // Disable for performance
-        .git_global(false)
-        .git_exclude(false)
-        .threads(thread_count)
-        .build_parallel()
-        .run(|| {
-            let globset = globset.clone();
-            let matched = Arc::clone(&matched);
-            let remaining = Arc::clone(&remaining);
-            let idx_mapping = idx_mapping.clone();
-
-            Box::new(move |entry: Result<DirEntry, ignore::Error>| {
-                let dir_entry = match entry {
-                    Ok(de) => de,
-                    Err(_) => return WalkState::Continue,
-                };
-
-                let path = dir_entry.path();
+    })?;
 
-                // Skip .git directory
-                if dir_entry.file_type().is_some_and(|ft| ft.is_dir())
-                    && path.file_name().is_some_and(|name| name == ".git")
-                {
-                    return WalkState::Skip;
-                }
+    // Submodules that are not checked out are never walked
+    for submodule in &submodules {
+        record_matches(&matcher, &seen, &settled_by, &pending, submodule, true);
+    }
 
+    let walked = walk_worktree(
+        repo_path,
+        options,
+        &excludes,
+        &submodules,
+        thread_count,
+        || {
+            let (matcher, seen, settled_by, pending) = (&matcher, &seen, &settled_by, &pending);
+            move |rel_path: &Path, kind| {
                 // OPTIMIZATION: Early exit check
-                if remaining.load(Ordering::Relaxed) == 0 {
+                if pending.is_empty() {
                     return WalkState::Quit;
                 }
-
-                if let Ok(rel_path) = path.strip_prefix(repo_path) {
-                    let matches = globset.matches(rel_path);
-                    if !matches.is_empty() {
-                        for glob_idx in matches {
-                            // Map from globset index to wildcard rule index
-                            if let Some(&rule_idx) = idx_mapping.get(&glob_idx) {
-                                // Only decrement remaining if this is the first match
-                                if matched[rule_idx].fetch_add(1, Ordering::Relaxed) == 0 {
-                                    remaining.fetch_sub(1, Ordering::Relaxed);
-                                }
-                            }
-                        }
-                    }
+                // OPTIMIZATION: Skip subtrees no unsettled rule can match
+                if !pending.reaches(rel_path) {
+                    return WalkState::Skip;
                 }
-
+                let is_dir = kind == EntryKind::Directory;
+                record_matches(matcher, seen, settled_by, pending, rel_path, is_dir);
                 WalkState::Continue
-            })
-        });
+            }
+        },
+    );
+
+    // An unreadable entry could hide the only match for a rule, so the
+    // result is only trustworthy if every rule matched anyway
+    if let Err(err) = walked {
+        if !pending.is_empty() {
+            return Err(err);
+        }
+        reporter.error("exists", &err);
+    }
 
-    // Collect unmatched wildcard rules
+    // Collect unmatched and unsettled wildcard rules
     for (idx, rule) in wildcard_rules.iter().enumerate() {
-        if matched[idx].load(Ordering::Relaxed) == 0 {
-            missing.push((*rule).clone());
+        let issue = match seen[idx].load(Ordering::Relaxed) {
+            0 => EntryIssue::Missing,
+            flags if flags & settled_by[idx] == 0 => EntryIssue::NotADirectory,
+            _ => continue,
+        };
+        findings.push(EntryFinding {
+            rule: (*rule).clone(),
+            issue,
+        });
+    }
+
+    // Report in the order of the rules, like `check_entries`
+    findings.sort_by_key(|finding| finding.rule.line_number);
+    Ok(findings)
+}
+
+/// The rules that are not settled yet, filed under the literal prefix of
+/// their pattern so the walk can tell which subtrees still matter.
+struct PendingRules {
+    /// Directory tree of the prefixes; the root is node 0
+    nodes: Vec<PrefixNode>,
+    /// Node of each rule's prefix
+    rule_nodes: Vec<usize>,
+}
+
+#[derive(Default)]
+struct PrefixNode {
+    parent: usize,
+    children: FxHashMap<OsString, usize>,
+    /// Unsettled rules with exactly this prefix
+    here: AtomicUsize,
+    /// Unsettled rules with this prefix or one below it
+    below: AtomicUsize,
+}
+
+impl PendingRules {
+    fn new(matcher: &RuleMatcher, num_rules: usize) -> Self {
+        let mut nodes = vec![PrefixNode::default()];
+        let mut rule_nodes = Vec::with_capacity(num_rules);
+
+        for rule_idx in 0..num_rules {
+            let mut node = 0;
+            *nodes[node].below.get_mut() += 1;
+            for component in matcher.literal_prefix(rule_idx).components() {
+                let Component::Normal(name) = component else {
+                    continue;
+                };
+                node = match nodes[node].children.get(name) {
+                    Some(&child) => child,
+                    None => {
+                        let child = nodes.len();
+                        nodes.push(PrefixNode {
+                            parent: node,
+                            ..PrefixNode::default()
+                        });
+                        nodes[node].children.insert(name.to_os_string(), child);
+                        child
+                    }
+                };
+                *nodes[node].below.get_mut() += 1;
+            }
+            *nodes[node].here.get_mut() += 1;
+            rule_nodes.push(node);
+        }
+
+        PendingRules { nodes, rule_nodes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.nodes[0].below.load(Ordering::Relaxed) == 0
+    }
+
+    /// Whether an unsettled rule can match `rel_path` or anything below it:
+    /// its prefix is above the path, or inside it.
+    fn reaches(&self, rel_path: &Path) -> bool {
+        let mut node = 0;
+        for component in rel_path.components() {
+            if self.nodes[node].here.load(Ordering::Relaxed) > 0 {
+                return true;
+            }
+            let Component::Normal(name) = component else {
+                continue;
+            };
+            match self.nodes[node].children.get(name) {
+                Some(&child) => node = child,
+                None => return false,
+            }
+        }
+        self.nodes[node].below.load(Ordering::Relaxed) > 0
+    }
+
+    /// Counts a rule as settled. Must be called once per rule.
+    fn settle(&self, rule_idx: usize) {
+        let mut node = self.rule_nodes[rule_idx];
+        self.nodes[node].here.fetch_sub(1, Ordering::Relaxed);
+        loop {
+            self.nodes[node].below.fetch_sub(1, Ordering::Relaxed);
+            if node == 0 {
+                break;
+            }
+            node = self.nodes[node].parent;
+        }
+    }
+}
+
+/// Marks the wildcard rules that `rel_path` matches as seen, and settles
+/// the rules it is the first to settle.
+fn record_matches(
+    matcher: &RuleMatcher,
+    seen: &[AtomicU8],
+    settled_by: &[u8],
+    pending: &PendingRules,
+    rel_path: &Path,
+    is_dir: bool,
+) {
+    for (rule_idx, kind) in matcher.matches_with_kind(rel_path) {
+        // Anything inside a matched directory proves the directory exists
+        let flag = if is_dir || kind == MatchKind::Contents {
+            SEEN_DIRECTORY
+        } else {
+            SEEN_FILE
+        };
+        let previous = seen[rule_idx].fetch_or(flag, Ordering::Relaxed);
+        // Only settle the rule the first time
+        let settles = settled_by[rule_idx];
+        if previous & settles == 0 && flag & settles != 0 {
+            pending.settle(rule_idx);
         }
     }
+}
+
+/// The submodule a rule's pattern reaches inside of. Only patterns that
+/// start at the repository root count; others also match outside it.
+fn reached_submodule<'a>(
+    rule: &CodeOwnerRule,
+    submodules: &'a FxHashSet<PathBuf>,
+) -> Option<&'a Path> {
+    // A `/` at the start or in the middle anchors a pattern
+    let from_root = rule.original_path.starts_with('/') || rule.pattern.contains('/');
+    if submodules.is_empty() || !from_root {
+        return None;
+    }
+    containing_submodule(Path::new(&rule.pattern), submodules)
+}
+
+/// The flags that settle a rule: a directory pattern waits for a directory,
+/// anything else for its first match.
+fn settled_by(rule: &CodeOwnerRule) -> u8 {
+    if is_directory_rule(rule) {
+        SEEN_DIRECTORY
+    } else {
+        SEEN_FILE | SEEN_DIRECTORY
+    }
+}
 
-    Ok(missing)
+fn is_directory_rule(rule: &CodeOwnerRule) -> bool {
+    rule.original_path.ends_with('/')
+}
+
+/// Anchored patterns without wildcards name exactly one path. Non-anchored
+/// ones like "main.rs" can match anywhere and need glob matching.
+fn is_direct_rule(rule: &CodeOwnerRule) -> bool {
+    !rule.pattern.contains(['*', '?', '[', ']']) && rule.original_path.starts_with('/')
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Error;
     use crate::parser::CodeOwnerRule;
     use std::fs;
     use tempfile::tempdir;
@@ -205,6 +514,7 @@ mod tests {
             pattern: pattern.trim_matches('/').to_string(),
             original_path: original.to_string(),
             owners: vec!["@team".to_string()],
+            line_number: 0,
         }
     }
 
@@ -300,6 +610,216 @@ mod tests {
         assert_eq!(result.len(), 1);
     }
 
+    #[test]
+    fn prunes_subtrees_without_pending_rules() {
+        let rules = vec![
+            rule("services/payments/**/*.rs", "/services/payments/**/*.rs"),
+            rule("services/payments/*.toml", "/services/payments/*.toml"),
+            rule("docs/*.md", "docs/*.md"),
+        ];
+        let matcher = RuleMatcher::new(&rules).unwrap();
+        let pending = PendingRules::new(&matcher, rules.len());
+
+        assert!(pending.reaches(Path::new("")));
+        assert!(pending.reaches(Path::new("services")));
+        assert!(pending.reaches(Path::new("services/payments/src/deep")));
+        assert!(!pending.reaches(Path::new("services/search")));
+        assert!(!pending.reaches(Path::new("vendor")));
+
+        pending.settle(0);
+        pending.settle(1);
+        assert!(!pending.reaches(Path::new("services")));
+        assert!(pending.reaches(Path::new("docs")));
+        pending.settle(2);
+        assert!(pending.is_empty());
+
+        // Pruning leaves the findings alone
+        let tmp = tempdir().unwrap();
+        let payments = tmp.path().join("services/payments/src");
+        fs::create_dir_all(&payments).unwrap();
+        fs::create_dir_all(tmp.path().join("services/search")).unwrap();
+        fs::write(payments.join("lib.rs"), "").unwrap();
+        fs::write(tmp.path().join("services/search/Cargo.toml"), "").unwrap();
+        let result = validate_directory(tmp.path(), &rules).unwrap();
+        let missing: Vec<&str> = result
+            .iter()
+            .map(|rule| rule.original_path.as_str())
+            .collect();
+        assert_eq!(missing, ["/services/payments/*.toml", "docs/*.md"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fails_when_directory_is_unreadable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempdir().unwrap();
+        let locked = tmp.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("secret.txt"), "content").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Root ignores permissions, so only assert when the walk is denied
+        let denied = fs::read_dir(&locked).is_err();
+        let rules = vec![rule("secret.txt", "secret.txt")];
+        let result = validate_directory(tmp.path(), &rules);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        if denied {
+            assert!(matches!(result, Err(Error::Walk(_))));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_unreadable_directories_when_every_rule_matched() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::sync::atomic::AtomicBool;
+
+        #[derive(Default)]
+        struct Recorder(AtomicBool);
+
+        impl Reporter for Recorder {
+            fn error(&self, validator: &str, _error: &dyn std::error::Error) {
+                assert_eq!(validator, "exists");
+                self.0.store(true, Ordering::Relaxed);
+            }
+        }
+
+        let tmp = tempdir().unwrap();
+        let locked = tmp.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("secret.txt"), "").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Root ignores permissions, so only assert when the walk is denied.
+        // The rule settles on the directory itself, before it is read.
+        let denied = fs::read_dir(&locked).is_err();
+        let rules = vec![rule("locked", "locked/")];
+        let recorder = Recorder::default();
+        let result = validate_entries_with(tmp.path(), &rules, FileOptions::DEFAULT, &recorder);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(result.unwrap().is_empty());
+        assert_eq!(recorder.0.load(Ordering::Relaxed), denied);
+    }
+
+    #[test]
+    fn matches_paths_with_spaces_and_hashes() {
+        let tmp = tempdir().unwrap();
+        let folder = tmp.path().join("docs").join("My Folder");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("#1.md"), "notes").unwrap();
+
+        let (rules, invalids) = crate::parser::parse_str(
+            "docs/My\\ Folder/ @docs\n/docs/My\\ Folder/\\#1.md @a # note\n*\\ Folder/ @b\n",
+        );
+        assert!(invalids.is_empty());
+        assert_eq!(rules.len(), 3);
+        let result = validate_directory(tmp.path(), &rules).unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn flags_directory_patterns_that_match_files() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("docs"), "not a directory").unwrap();
+        fs::write(tmp.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::create_dir_all(tmp.path().join("a/build")).unwrap();
+        fs::write(tmp.path().join("b-build"), "").unwrap();
+
+        let rules = vec![
+            rule("docs", "/docs/"),
+            rule("src/main.rs", "/src/main.rs/"),
+            rule("src", "/src/"),
+            rule("main.rs", "main.rs/"),
+            rule("build", "build/"),
+        ];
+        let issues: Vec<(String, EntryIssue)> = validate_entries(tmp.path(), &rules)
+            .unwrap()
+            .into_iter()
+            .map(|finding| (finding.rule.original_path, finding.issue))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("/docs/".to_string(), EntryIssue::NotADirectory),
+                ("/src/main.rs/".to_string(), EntryIssue::NotADirectory),
+                ("main.rs/".to_string(), EntryIssue::NotADirectory),
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_anchored_patterns_that_name_directories() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("config")).unwrap();
+        fs::write(tmp.path().join("config/app.toml"), "").unwrap();
+
+        let rules = vec![rule("config", "/config"), rule("config", "config")];
+        let findings = validate_entries(tmp.path(), &rules).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule.original_path, "/config");
+        assert_eq!(findings[0].issue, EntryIssue::ImplicitDirectory);
+
+        // Missing rules still show up through validate_directory alone
+        assert!(validate_directory(tmp.path(), &rules).unwrap().is_empty());
+    }
+
+    #[test]
+    fn checks_tracked_files_from_the_git_index() {
+        use crate::git::tests::git;
+
+        let tmp = tempdir().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::create_dir_all(tmp.path().join("node_modules/pkg")).unwrap();
+        fs::write(tmp.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(tmp.path().join("node_modules/pkg/index.js"), "").unwrap();
+        fs::write(tmp.path().join("scratch.txt"), "").unwrap();
+        git(tmp.path(), &["add", "src"]);
+
+        let rules = vec![
+            rule("src", "/src/"),
+            rule("src", "/src"),
+            rule("*.js", "*.js"),
+            rule("scratch.txt", "scratch.txt"),
+            rule("main.rs", "main.rs/"),
+        ];
+        let issues: Vec<(String, EntryIssue)> = validate_entries_from(
+            tmp.path(),
+            &rules,
+            FileSource::GitIndex,
+            FileOptions::DEFAULT,
+            &NoopReporter,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|finding| (finding.rule.original_path, finding.issue))
+        .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("/src".to_string(), EntryIssue::ImplicitDirectory),
+                ("*.js".to_string(), EntryIssue::Missing),
+                ("scratch.txt".to_string(), EntryIssue::Missing),
+                ("main.rs/".to_string(), EntryIssue::NotADirectory),
+            ]
+        );
+
+        // The worktree still sees untracked files
+        let worktree = validate_entries_from(
+            tmp.path(),
+            &rules,
+            FileSource::Worktree,
+            FileOptions::DEFAULT,
+            &NoopReporter,
+        )
+        .unwrap();
+        assert_eq!(worktree.len(), 2);
+    }
+
     #[test]
     fn handles_complex_wildcards() {
         let tmp = tempdir().unwrap();
@@ -312,4 +832,174 @@ mod tests {
         let result = validate_directory(tmp.path(), &rules).unwrap();
         assert!(result.is_empty());
     }
+
+    #[test]
+    fn excluded_paths_do_not_count() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("vendor/lib")).unwrap();
+        fs::create_dir_all(tmp.path().join("web/node_modules/pkg")).unwrap();
+        fs::write(tmp.path().join("vendor/lib/a.go"), "").unwrap();
+        fs::write(tmp.path().join("web/node_modules/pkg/index.js"), "").unwrap();
+        fs::write(tmp.path().join("web/app.ts"), "").unwrap();
+
+        let (rules, _) = crate::parser::parse_str(
+            "/vendor/ @a
+*.js @a
+*.ts @a
+/web/app.ts @a
+",
+        );
+        let options = FileOptions {
+            exclude: vec!["/vendor/".to_string(), "node_modules".to_string()],
+            ..FileOptions::default()
+        };
+        let missing: Vec<String> =
+            validate_entries_with(tmp.path(), &rules, &options, &NoopReporter)
+                .unwrap()
+                .into_iter()
+                .map(|finding| finding.rule.original_path)
+                .collect();
+        assert_eq!(missing, vec!["/vendor/", "*.js"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_policy_decides_whether_links_exist() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("real")).unwrap();
+        fs::write(tmp.path().join("real/data.txt"), "").unwrap();
+        std::os::unix::fs::symlink("real", tmp.path().join("linked")).unwrap();
+        std::os::unix::fs::symlink("nowhere", tmp.path().join("broken")).unwrap();
+
+        let (rules, _) = crate::parser::parse_str(
+            "/broken @a
+/linked @a
+linked/*.txt @a
+brok* @a
+",
+        );
+        let findings = |symlinks| -> Vec<(String, EntryIssue)> {
+            let options = FileOptions {
+                symlinks,
+                ..FileOptions::default()
+            };
+            validate_entries_with(tmp.path(), &rules, &options, &NoopReporter)
+                .unwrap()
+                .into_iter()
+                .map(|finding| (finding.rule.original_path, finding.issue))
+                .collect()
+        };
+
+        let missing = |pattern: &str| (pattern.to_string(), EntryIssue::Missing);
+        assert_eq!(
+            findings(SymlinkPolicy::Entry),
+            vec![missing("linked/*.txt")]
+        );
+        assert_eq!(
+            findings(SymlinkPolicy::Follow),
+            vec![
+                missing("/broken"),
+                ("/linked".to_string(), EntryIssue::ImplicitDirectory),
+                missing("brok*"),
+            ]
+        );
+        assert_eq!(
+            findings(SymlinkPolicy::Skip),
+            vec![
+                missing("/broken"),
+                missing("/linked"),
+                missing("linked/*.txt"),
+                missing("brok*"),
+            ]
+        );
+    }
+
+    #[test]
+    fn submodules_exist_and_rules_inside_them_are_flagged() {
+        let tmp = tempdir().unwrap();
+        crate::git::tests::git(tmp.path(), &["init", "-q"]);
+        fs::write(
+            tmp.path().join(".gitmodules"),
+            "[submodule \"foo\"]\n\tpath = third_party/foo\n",
+        )
+        .unwrap();
+        crate::git::tests::git(tmp.path(), &["add", ".gitmodules"]);
+        crate::git::tests::git(
+            tmp.path(),
+            &[
+                "update-index",
+                "--add",
+                "--cacheinfo",
+                "160000,0123456789012345678901234567890123456789,third_party/foo",
+            ],
+        );
+
+        let (rules, _) = crate::parser::parse_str(
+            "/third_party/foo/ @a\nthird_party/ @a\n/third_party/foo/src/ @a\nthird_party/foo/*.c @a\n*.c @a\n",
+        );
+        let inside = |pattern: &str| {
+            (
+                pattern.to_string(),
+                EntryIssue::InsideSubmodule {
+                    submodule: PathBuf::from("third_party/foo"),
+                },
+            )
+        };
+        // The submodule is not checked out, so neither source has its files
+        for source in [FileSource::GitIndex, FileSource::Worktree] {
+            let findings: Vec<(String, EntryIssue)> = validate_entries_from(
+                tmp.path(),
+                &rules,
+                source,
+                FileOptions::DEFAULT,
+                &NoopReporter,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|finding| (finding.rule.original_path, finding.issue))
+            .collect();
+            assert_eq!(
+                findings,
+                vec![
+                    inside("/third_party/foo/src/"),
+                    inside("third_party/foo/*.c"),
+                    ("*.c".to_string(), EntryIssue::Missing),
+                ],
+                "{:?}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn counts_matched_and_effective_files_per_rule() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("src/api")).unwrap();
+        fs::write(tmp.path().join("src/lib.rs"), "").unwrap();
+        fs::write(tmp.path().join("src/api/mod.rs"), "").unwrap();
+        fs::write(tmp.path().join("README.md"), "").unwrap();
+
+        let (rules, _) =
+            crate::parser::parse_str("* @all\n/src/ @core\n/src/api/ @api\n*.txt @a\n");
+        let files =
+            FileIndex::build(tmp.path(), FileSource::Worktree, FileOptions::DEFAULT).unwrap();
+        let stats = rule_stats(&rules, &files, 1).unwrap();
+
+        assert_eq!(stats.files, 3);
+        let counts: Vec<(usize, usize, Vec<PathBuf>)> = stats
+            .rules
+            .iter()
+            .map(|rule| (rule.matched, rule.effective, rule.examples.clone()))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                (3, 1, vec![PathBuf::from("README.md")]),
+                (2, 1, vec![PathBuf::from("src/api/mod.rs")]),
+                (1, 1, vec![PathBuf::from("src/api/mod.rs")]),
+                (0, 0, vec![]),
+            ]
+        );
+        assert!((stats.share(&stats.rules[1]) - 2.0 / 3.0).abs() < f64::EPSILON);
+    }
 }
@@ -0,0 +1,121 @@
+use crate::error::Result;
+use crate::file_index::FileIndex;
+use crate::matcher::RuleMatcher;
+use crate::parser::CodeOwnerRule;
+use std::path::PathBuf;
+
+/// A rule that only matches files git ignores, so it never applies to a
+/// change under review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoredFinding {
+    pub rule: CodeOwnerRule,
+    /// How many ignored files the rule matches
+    pub ignored: usize,
+    /// The first of them, as an example
+    pub example: PathBuf,
+}
+
+/// Finds rules whose matches are all ignored files that git does not
+/// track. Only files from the working tree or a manifest can be ignored;
+/// with the index or a revision as the source such rules match nothing,
+/// which the `exists` check reports. Rules that match nothing at all are
+/// left to that check too.
+pub fn validate_gitignored(
+    rules: &[CodeOwnerRule],
+    files: &FileIndex,
+) -> Result<Vec<IgnoredFinding>> {
+    if !files.has_ignored() {
+        return Ok(Vec::new());
+    }
+
+    let matcher = RuleMatcher::new(rules)?;
+    let mut kept = vec![false; rules.len()];
+    let mut matched: Vec<(usize, Option<PathBuf>)> = vec![(0, None); rules.len()];
+    for (file, is_ignored) in files.files_with_ignored() {
+        for idx in matcher.matches(&file) {
+            if is_ignored {
+                let (count, example) = &mut matched[idx];
+                *count += 1;
+                example.get_or_insert_with(|| file.clone());
+            } else {
+                kept[idx] = true;
+            }
+        }
+    }
+
+    Ok(rules
+        .iter()
+        .zip(kept)
+        .zip(matched)
+        .filter_map(|((rule, kept), (ignored, example))| {
+            let example = example.filter(|_| !kept)?;
+            Some(IgnoredFinding {
+                rule: rule.clone(),
+                ignored,
+                example,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{FileOptions, FileSource};
+    use crate::git::tests::git;
+    use crate::parser::parse_str;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn worktree(repo_path: &Path) -> FileIndex {
+        FileIndex::build(repo_path, FileSource::Worktree, FileOptions::DEFAULT).unwrap()
+    }
+
+    #[test]
+    fn flags_rules_that_only_match_ignored_files() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("dist")).unwrap();
+        fs::create_dir_all(tmp.path().join("logs")).unwrap();
+        fs::write(tmp.path().join(".gitignore"), "dist/\n*.log\n").unwrap();
+        fs::write(tmp.path().join("dist/app.js"), "").unwrap();
+        fs::write(tmp.path().join("dist/app.css"), "").unwrap();
+        fs::write(tmp.path().join("logs/debug.log"), "").unwrap();
+        fs::write(tmp.path().join("logs/README.md"), "").unwrap();
+        fs::write(tmp.path().join("app.js"), "").unwrap();
+
+        let (rules, _) = parse_str("/dist/ @a\n*.log @a\n/logs/ @a\n*.js @a\n/missing/ @a\n");
+        let findings: Vec<(String, usize, PathBuf)> =
+            validate_gitignored(&rules, &worktree(tmp.path()))
+                .unwrap()
+                .into_iter()
+                .map(|finding| (finding.rule.original_path, finding.ignored, finding.example))
+                .collect();
+        assert_eq!(
+            findings,
+            vec![
+                ("/dist/".to_string(), 2, PathBuf::from("dist/app.css")),
+                ("*.log".to_string(), 1, PathBuf::from("logs/debug.log")),
+            ]
+        );
+    }
+
+    #[test]
+    fn tracked_files_are_not_ignored() {
+        let tmp = tempdir().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        fs::create_dir_all(tmp.path().join("dist")).unwrap();
+        fs::write(tmp.path().join(".gitignore"), "dist/\n").unwrap();
+        fs::write(tmp.path().join("dist/app.js"), "").unwrap();
+        git(tmp.path(), &["add", "-f", "dist/app.js"]);
+        fs::write(tmp.path().join("dist/app.css"), "").unwrap();
+
+        let (rules, _) = parse_str("/dist/app.js @a\n/dist/app.css @a\n");
+        let findings: Vec<String> = validate_gitignored(&rules, &worktree(tmp.path()))
+            .unwrap()
+            .into_iter()
+            .map(|finding| finding.rule.original_path)
+            .collect();
+        assert_eq!(findings, vec!["/dist/app.css".to_string()]);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::discovery::Forge;
+use crate::parser::CodeOwnerRule;
+
+/// Share of a file-wide limit at which a warning is raised.
+pub const WARN_RATIO: f64 = 0.8;
+
+/// Limits a forge puts on CODEOWNERS files. `None` means no limit is
+/// documented.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Bytes; above this the whole file is ignored
+    pub max_file_size: Option<usize>,
+    /// Bytes per line, excluding the line ending
+    pub max_line_length: Option<usize>,
+    pub max_rules: Option<usize>,
+    pub max_owners_per_rule: Option<usize>,
+}
+
+impl Limits {
+    /// The limits `forge` documents. GitHub only documents the 3 MB file
+    /// size; neither forge documents a limit on line length, rule count or
+    /// owners per line, so those are only checked when configured.
+    pub fn for_forge(forge: Forge) -> Self {
+        match forge {
+            Forge::GitHub => Limits {
+                max_file_size: Some(3 * 1024 * 1024),
+                ..Limits::default()
+            },
+            Forge::GitLab => Limits::default(),
+        }
+    }
+
+    /// Limits set in `overrides` replace the ones in `self`.
+    pub fn with_overrides(self, overrides: &Limits) -> Self {
+        Limits {
+            max_file_size: overrides.max_file_size.or(self.max_file_size),
+            max_line_length: overrides.max_line_length.or(self.max_line_length),
+            max_rules: overrides.max_rules.or(self.max_rules),
+            max_owners_per_rule: overrides.max_owners_per_rule.or(self.max_owners_per_rule),
+        }
+    }
+}
+
+/// A limit that is exceeded, or for file-wide limits, nearly reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitFinding {
+    FileSize {
+        size: usize,
+        limit: usize,
+    },
+    RuleCount {
+        rules: usize,
+        limit: usize,
+    },
+    LineLength {
+        line_number: usize,
+        length: usize,
+        limit: usize,
+    },
+    OwnersPerRule {
+        line_number: usize,
+        owners: usize,
+        limit: usize,
+    },
+}
+
+impl LimitFinding {
+    /// Whether the limit is crossed rather than only approached.
+    pub fn is_exceeded(&self) -> bool {
+        match self {
+            LimitFinding::FileSize { size, limit } => size > limit,
+            LimitFinding::RuleCount { rules, limit } => rules > limit,
+            LimitFinding::LineLength { .. } | LimitFinding::OwnersPerRule { .. } => true,
+        }
+    }
+}
+
+pub fn validate_limits(
+    source: &[u8],
+    rules: &[CodeOwnerRule],
+    limits: &Limits,
+) -> Vec<LimitFinding> {
+    let mut findings = Vec::new();
+
+    if let Some(limit) = limits.max_file_size {
+        if approaching(source.len(), limit) {
+            findings.push(LimitFinding::FileSize {
+                size: source.len(),
+                limit,
+            });
+        }
+    }
+
+    if let Some(limit) = limits.max_rules {
+        if approaching(rules.len(), limit) {
+            findings.push(LimitFinding::RuleCount {
+                rules: rules.len(),
+                limit,
+            });
+        }
+    }
+
+    if let Some(limit) = limits.max_line_length {
+        for (idx, line) in source.split(|&byte| byte == b'\n').enumerate() {
+            let length = line.strip_suffix(b"\r").unwrap_or(line).len();
+            if length > limit {
+                findings.push(LimitFinding::LineLength {
+                    line_number: idx + 1,
+                    length,
+                    limit,
+                });
+            }
+        }
+    }
+
+    if let Some(limit) = limits.max_owners_per_rule {
+        for rule in rules.iter().filter(|rule| rule.owners.len() > limit) {
+            findings.push(LimitFinding::OwnersPerRule {
+                line_number: rule.line_number,
+                owners: rule.owners.len(),
+                limit,
+            });
+        }
+    }
+
+    findings
+}
+
+fn approaching(value: usize, limit: usize) -> bool {
+    value as f64 >= limit as f64 * WARN_RATIO
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_str;
+
+    #[test]
+    fn github_limits_the_file_size() {
+        let limits = Limits::for_forge(Forge::GitHub);
+        assert!(validate_limits(&[b'#'; 1024], &[], &limits).is_empty());
+
+        let near = vec![b'#'; 2_600_000];
+        let findings = validate_limits(&near, &[], &limits);
+        assert_eq!(findings.len(), 1);
+        assert!(!findings[0].is_exceeded());
+
+        let over = vec![b'#'; 3 * 1024 * 1024 + 1];
+        assert!(validate_limits(&over, &[], &limits)[0].is_exceeded());
+    }
+
+    #[test]
+    fn checks_lines_rules_and_owners_when_configured() {
+        let source = "* @a\n/src/ @a @b @c\n/a/very/long/path/ @a\r\n";
+        let (rules, _) = parse_str(source);
+        let limits = Limits {
+            max_line_length: Some(20),
+            max_rules: Some(3),
+            max_owners_per_rule: Some(2),
+            ..Limits::default()
+        };
+
+        assert_eq!(
+            validate_limits(source.as_bytes(), &rules, &limits),
+            vec![
+                LimitFinding::RuleCount { rules: 3, limit: 3 },
+                LimitFinding::LineLength {
+                    line_number: 3,
+                    length: 21,
+                    limit: 20
+                },
+                LimitFinding::OwnersPerRule {
+                    line_number: 2,
+                    owners: 3,
+                    limit: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn overrides_replace_forge_defaults() {
+        let limits = Limits::for_forge(Forge::GitHub).with_overrides(&Limits {
+            max_rules: Some(10),
+            ..Limits::default()
+        });
+        assert_eq!(limits.max_file_size, Some(3 * 1024 * 1024));
+        assert_eq!(limits.max_rules, Some(10));
+    }
+}
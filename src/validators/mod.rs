@@ -1,5 +1,14 @@
+pub mod context;
+pub mod coverage;
 pub mod duplicate_patterns;
 pub mod exists;
+pub mod gitignored;
+pub mod limits;
+pub mod no_owners;
+pub mod overbroad;
+pub mod path_normalization;
+pub mod pattern_style;
 pub mod validator;
 
+pub use context::ValidationContext;
 pub use validator::{run_validator, ValidatorArgs};
@@ -0,0 +1,46 @@
+use crate::parser::CodeOwnerRule;
+
+/// Returns the rules without owners, except those whose pattern is in
+/// `allowed`. Allow-list entries match the pattern as written in the file
+/// or its normalized form, so both `/vendor/` and `vendor` allow `/vendor/`.
+pub fn validate_no_owners(rules: &[CodeOwnerRule], allowed: &[String]) -> Vec<CodeOwnerRule> {
+    rules
+        .iter()
+        .filter(|rule| rule.is_unowned())
+        .filter(|rule| {
+            !allowed.iter().any(|entry| {
+                *entry == rule.original_path || entry.trim_matches('/') == rule.pattern
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(original: &str, owners: &[&str]) -> CodeOwnerRule {
+        CodeOwnerRule {
+            pattern: original.trim_matches('/').to_string(),
+            original_path: original.to_string(),
+            owners: owners.iter().map(|owner| owner.to_string()).collect(),
+            line_number: 0,
+        }
+    }
+
+    #[test]
+    fn reports_rules_without_owners() {
+        let rules = vec![rule("/src/", &["@core"]), rule("/src/generated/", &[])];
+        let result = validate_no_owners(&rules, &[]);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].original_path, "/src/generated/");
+    }
+
+    #[test]
+    fn skips_allowed_patterns() {
+        let rules = vec![rule("/vendor/", &[]), rule("/third_party/", &[])];
+        let allowed = vec!["/vendor/".to_string(), "third_party".to_string()];
+        assert!(validate_no_owners(&rules, &allowed).is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+use crate::error::Result;
+use crate::file_index::FileIndex;
+use crate::matcher::RuleMatcher;
+use crate::parser::CodeOwnerRule;
+use rustc_hash::FxHashMap;
+
+/// Earlier rules a rule may take files from before it is flagged, unless
+/// configured otherwise.
+pub const DEFAULT_MAX_OVERRIDDEN_RULES: usize = 10;
+
+/// When a rule owns too much. `None` turns a threshold off.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Breadth {
+    /// Percentage of the repository's files a rule may be the effective
+    /// rule for
+    pub max_share: Option<f64>,
+    /// Files a rule may be the effective rule for
+    pub max_files: Option<usize>,
+    /// Earlier rules with other owners that a rule may take files from
+    pub max_overridden_rules: Option<usize>,
+}
+
+impl Default for Breadth {
+    fn default() -> Self {
+        Breadth {
+            max_share: None,
+            max_files: None,
+            max_overridden_rules: Some(DEFAULT_MAX_OVERRIDDEN_RULES),
+        }
+    }
+}
+
+/// A rule that owns more of the repository than the thresholds allow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverbroadFinding {
+    pub rule: CodeOwnerRule,
+    /// Files the rule is the effective rule for
+    pub effective: usize,
+    /// Files in the repository
+    pub files: usize,
+    /// Thresholds the rule crosses
+    pub exceeded: Vec<Exceeded>,
+    /// Earlier rules that would own some of the rule's files without it,
+    /// with how many, in file order
+    pub overridden: Vec<(CodeOwnerRule, usize)>,
+    /// Owners that lose files to the rule, with how many, most first
+    pub losers: Vec<(String, usize)>,
+}
+
+impl OverbroadFinding {
+    /// Percentage of the repository's files the rule owns.
+    pub fn share(&self) -> f64 {
+        match self.files {
+            0 => 0.0,
+            files => self.effective as f64 * 100.0 / files as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exceeded {
+    Share { limit: f64 },
+    Files { limit: usize },
+    OverriddenRules { limit: usize },
+}
+
+/// Finds rules with owners whose effective ownership crosses a threshold
+/// in `breadth`. A rule overrides an earlier one for a file when the file
+/// would otherwise belong to the earlier rule and the owners differ.
+pub fn validate_overbroad(
+    rules: &[CodeOwnerRule],
+    files: &FileIndex,
+    breadth: &Breadth,
+) -> Result<Vec<OverbroadFinding>> {
+    let matcher = RuleMatcher::new(rules)?;
+    let files = files.ownable_paths();
+
+    let mut effective = vec![0usize; rules.len()];
+    // Per rule: the rules it overrides and the owners it takes files from
+    let mut overridden: Vec<Vec<(usize, usize)>> = vec![Vec::new(); rules.len()];
+    let mut losers: Vec<FxHashMap<&str, usize>> = vec![FxHashMap::default(); rules.len()];
+    for file in &files {
+        let matches = matcher.matches(file);
+        let Some((&owner, earlier)) = matches.split_last() else {
+            continue;
+        };
+        effective[owner] += 1;
+
+        let Some(&previous) = earlier.last() else {
+            continue;
+        };
+        if rules[previous].owners == rules[owner].owners {
+            continue;
+        }
+        match overridden[owner]
+            .iter_mut()
+            .find(|(idx, _)| *idx == previous)
+        {
+            Some((_, count)) => *count += 1,
+            None => overridden[owner].push((previous, 1)),
+        }
+        for lost in &rules[previous].owners {
+            if !rules[owner].owners.contains(lost) {
+                *losers[owner].entry(lost.as_str()).or_default() += 1;
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (idx, rule) in rules.iter().enumerate() {
+        if rule.owners.is_empty() {
+            // Unowning on purpose is the no_owners check's business
+            continue;
+        }
+        let share = match files.len() {
+            0 => 0.0,
+            total => effective[idx] as f64 * 100.0 / total as f64,
+        };
+        let mut exceeded = Vec::new();
+        if let Some(limit) = breadth.max_share.filter(|&limit| share > limit) {
+            exceeded.push(Exceeded::Share { limit });
+        }
+        if let Some(limit) = breadth.max_files.filter(|&limit| effective[idx] > limit) {
+            exceeded.push(Exceeded::Files { limit });
+        }
+        if let Some(limit) = breadth
+            .max_overridden_rules
+            .filter(|&limit| overridden[idx].len() > limit)
+        {
+            exceeded.push(Exceeded::OverriddenRules { limit });
+        }
+        if exceeded.is_empty() {
+            continue;
+        }
+
+        let mut lost: Vec<(String, usize)> = losers[idx]
+            .iter()
+            .map(|(owner, count)| (owner.to_string(), *count))
+            .collect();
+        lost.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        findings.push(OverbroadFinding {
+            rule: rule.clone(),
+            effective: effective[idx],
+            files: files.len(),
+            exceeded,
+            overridden: overridden[idx]
+                .iter()
+                .map(|&(previous, count)| (rules[previous].clone(), count))
+                .collect(),
+            losers: lost,
+        });
+    }
+
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{FileOptions, FileSource};
+    use crate::parser::parse_str;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn flags_late_rules_that_take_over_files() {
+        let tmp = tempdir().unwrap();
+        for dir in ["api", "web", "docs"] {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+            fs::write(tmp.path().join(dir).join("config.json"), "").unwrap();
+            fs::write(tmp.path().join(dir).join("main.rs"), "").unwrap();
+        }
+        for file in ["README.md", "LICENSE", "Makefile"] {
+            fs::write(tmp.path().join(file), "").unwrap();
+        }
+
+        let (rules, _) =
+            parse_str("* @all\n/api/ @api\n/web/ @web\n/docs/ @docs @platform\n*.json @platform\n");
+        let breadth = Breadth {
+            max_overridden_rules: Some(2),
+            ..Breadth::default()
+        };
+        let files =
+            FileIndex::build(tmp.path(), FileSource::Worktree, FileOptions::DEFAULT).unwrap();
+        let findings = validate_overbroad(&rules, &files, &breadth).unwrap();
+
+        assert_eq!(findings.len(), 1);
+        let finding = &findings[0];
+        assert_eq!(finding.rule.original_path, "*.json");
+        assert_eq!((finding.effective, finding.files), (3, 9));
+        assert_eq!(
+            finding.exceeded,
+            vec![Exceeded::OverriddenRules { limit: 2 }]
+        );
+        assert_eq!(finding.overridden.len(), 3);
+        assert_eq!(
+            finding.losers,
+            vec![
+                ("@api".to_string(), 1),
+                ("@docs".to_string(), 1),
+                ("@web".to_string(), 1),
+            ]
+        );
+
+        // The catch-all and `*.json` own a third of the repository each
+        let breadth = Breadth {
+            max_share: Some(30.0),
+            max_files: Some(2),
+            max_overridden_rules: None,
+        };
+        let flagged: Vec<(String, Vec<Exceeded>)> = validate_overbroad(&rules, &files, &breadth)
+            .unwrap()
+            .into_iter()
+            .map(|finding| (finding.rule.original_path, finding.exceeded))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (
+                    "*".to_string(),
+                    vec![
+                        Exceeded::Share { limit: 30.0 },
+                        Exceeded::Files { limit: 2 }
+                    ]
+                ),
+                (
+                    "*.json".to_string(),
+                    vec![
+                        Exceeded::Share { limit: 30.0 },
+                        Exceeded::Files { limit: 2 }
+                    ]
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use crate::file_index::FileIndex;
+use crate::parser::CodeOwnerRule;
+use std::fmt;
+use std::path::Path;
+
+/// Top-level directories that point at a filesystem path rather than a
+/// repository path, unless the repository happens to contain them. This is
+/// a guess, so such findings are only warnings.
+const FILESYSTEM_ROOTS: &[&str] = &[
+    "home", "Users", "root", "tmp", "var", "opt", "mnt", "private", "github", "builds",
+];
+
+/// A way a pattern is written that the forge does not resolve like a
+/// filesystem would.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathIssue {
+    /// A `.` segment, e.g. `./src/`
+    CurrentDir,
+    /// A `..` segment, e.g. `src/../docs`
+    ParentDir,
+    /// An empty segment, e.g. `src//lib`
+    DoubledSlash,
+    /// A backslash used as a separator, e.g. `src\lib`
+    Backslash,
+    /// An absolute filesystem path, e.g. `/home/ci/repo/src`
+    AbsolutePath,
+}
+
+impl PathIssue {
+    /// What the forge actually does with a pattern written this way.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            PathIssue::CurrentDir => {
+                "`.` is not resolved: the pattern looks for a directory literally named `.` and matches nothing"
+            }
+            PathIssue::ParentDir => {
+                "`..` is not resolved: the pattern looks for a directory literally named `..` and matches nothing"
+            }
+            PathIssue::DoubledSlash => {
+                "no path has an empty segment, so a doubled `/` makes the pattern match nothing"
+            }
+            PathIssue::Backslash => {
+                "`\\` escapes the next character instead of separating directories, so `src\\lib` only matches `srclib`"
+            }
+            PathIssue::AbsolutePath => {
+                "patterns are relative to the repository root; a leading `/` anchors there, not at the filesystem root"
+            }
+        }
+    }
+}
+
+impl fmt::Display for PathIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathIssue::CurrentDir => f.write_str("`.` segment"),
+            PathIssue::ParentDir => f.write_str("`..` segment"),
+            PathIssue::DoubledSlash => f.write_str("doubled slash"),
+            PathIssue::Backslash => f.write_str("backslash separator"),
+            PathIssue::AbsolutePath => f.write_str("absolute filesystem path"),
+        }
+    }
+}
+
+/// A rule whose pattern is not written as a normalized repository path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathFinding {
+    pub rule: CodeOwnerRule,
+    pub issues: Vec<PathIssue>,
+    /// The pattern as it should be written, when it can be worked out
+    pub replacement: Option<String>,
+}
+
+/// Whether any rule starts with a directory from `FILESYSTEM_ROOTS`, which
+/// `validate_path_normalization` needs the repository's files to judge.
+pub fn needs_files(rules: &[CodeOwnerRule]) -> bool {
+    rules
+        .iter()
+        .any(|rule| filesystem_root(&rule.original_path).is_some())
+}
+
+/// Finds patterns that are not normalized repository paths. `files` tells
+/// whether a directory like `/builds/` is part of the repository; without
+/// it such patterns are trusted.
+pub fn validate_path_normalization(
+    repo_root: &Path,
+    rules: &[CodeOwnerRule],
+    files: Option<&FileIndex>,
+) -> Vec<PathFinding> {
+    let absolute_root = repo_root
+        .canonicalize()
+        .ok()
+        .and_then(|root| root.to_str().map(|root| root.replace('\\', "/")));
+
+    rules
+        .iter()
+        .filter_map(|rule| {
+            let (issues, replacement) =
+                check_pattern(&rule.original_path, files, absolute_root.as_deref())?;
+            Some(PathFinding {
+                rule: rule.clone(),
+                issues,
+                replacement,
+            })
+        })
+        .collect()
+}
+
+/// Finds the issues in a pattern as written and the normalized pattern.
+/// `absolute_root` is the repository's own absolute path, which can be
+/// stripped from absolute filesystem paths.
+fn check_pattern(
+    original: &str,
+    files: Option<&FileIndex>,
+    absolute_root: Option<&str>,
+) -> Option<(Vec<PathIssue>, Option<String>)> {
+    let mut issues = Vec::new();
+    let mut pattern = replace_backslash_separators(original);
+    if pattern != original {
+        issues.push(PathIssue::Backslash);
+    }
+
+    let mut resolvable = true;
+    let stripped = absolute_root.and_then(|root| {
+        pattern
+            .strip_prefix(root)
+            .filter(|rest| rest.starts_with('/'))
+            .map(str::to_string)
+    });
+    if let Some(rest) = stripped {
+        issues.push(PathIssue::AbsolutePath);
+        pattern = rest;
+    } else if is_filesystem_path(&pattern, files) {
+        issues.push(PathIssue::AbsolutePath);
+        resolvable = false;
+    }
+
+    let is_directory = pattern.ends_with('/');
+    let mut is_anchored = pattern.starts_with('/');
+    let body = pattern.trim_start_matches('/').trim_end_matches('/');
+    if pattern.starts_with("//") || body.contains("//") {
+        issues.push(PathIssue::DoubledSlash);
+    }
+
+    let mut segments: Vec<&str> = Vec::new();
+    for (idx, segment) in body.split('/').enumerate() {
+        match segment {
+            "" => {}
+            "." => {
+                push_issue(&mut issues, PathIssue::CurrentDir);
+                // `./src` is meant relative to the root
+                is_anchored |= idx == 0;
+            }
+            ".." => {
+                push_issue(&mut issues, PathIssue::ParentDir);
+                if segments.pop().is_none() {
+                    // Points above the repository root
+                    resolvable = false;
+                }
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    if issues.is_empty() {
+        return None;
+    }
+
+    let replacement = (resolvable && !segments.is_empty()).then(|| {
+        format!(
+            "{}{}{}",
+            if is_anchored { "/" } else { "" },
+            segments.join("/"),
+            if is_directory { "/" } else { "" }
+        )
+    });
+    Some((issues, replacement))
+}
+
+fn push_issue(issues: &mut Vec<PathIssue>, issue: PathIssue) {
+    if !issues.contains(&issue) {
+        issues.push(issue);
+    }
+}
+
+/// Turns `\` into `/` where it precedes a plain path character. Escapes
+/// that mean something to CODEOWNERS or globset, like `\ ` or `\*`, are
+/// kept.
+fn replace_backslash_separators(pattern: &str) -> String {
+    let mut replaced = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            replaced.push(ch);
+            continue;
+        }
+        match chars.peek() {
+            Some(&next) if next.is_alphanumeric() || next == '_' || next == '-' => {
+                replaced.push('/')
+            }
+            Some(&next) => {
+                replaced.push(ch);
+                replaced.push(next);
+                chars.next();
+            }
+            None => replaced.push(ch),
+        }
+    }
+    replaced
+}
+
+/// Whether an anchored pattern looks like a path on the CI machine, e.g.
+/// `/home/runner/work/repo/src` or `C:/src`, rather than one in the repo.
+fn is_filesystem_path(pattern: &str, files: Option<&FileIndex>) -> bool {
+    let bytes = pattern.as_bytes();
+    if bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'/' {
+        return true;
+    }
+
+    match (filesystem_root(pattern), files) {
+        (Some(first), Some(files)) => files.kind(Path::new(first)).is_none(),
+        _ => false,
+    }
+}
+
+/// The first directory of an anchored pattern, if it is one of
+/// `FILESYSTEM_ROOTS`.
+fn filesystem_root(pattern: &str) -> Option<&str> {
+    let (first, _) = pattern.strip_prefix('/')?.split_once('/')?;
+    FILESYSTEM_ROOTS.contains(&first).then_some(first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{EntryKind, RepoEntry};
+    use std::path::PathBuf;
+
+    fn check(original: &str) -> Option<(Vec<PathIssue>, Option<String>)> {
+        check_pattern(original, Some(&FileIndex::default()), Some("/home/ci/repo"))
+    }
+
+    #[test]
+    fn leaves_normal_patterns_alone() {
+        for pattern in [
+            "/src/",
+            "*.rs",
+            "docs/**/*.md",
+            "My\\ Folder/",
+            "\\#1.md",
+            "src/\\*.rs",
+        ] {
+            assert_eq!(check(pattern), None, "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn normalizes_dot_segments() {
+        assert_eq!(
+            check("./src/"),
+            Some((vec![PathIssue::CurrentDir], Some("/src/".to_string())))
+        );
+        assert_eq!(
+            check("src/../docs"),
+            Some((vec![PathIssue::ParentDir], Some("docs".to_string())))
+        );
+        assert_eq!(check("../docs"), Some((vec![PathIssue::ParentDir], None)));
+    }
+
+    #[test]
+    fn normalizes_slashes() {
+        assert_eq!(
+            check("src//lib"),
+            Some((vec![PathIssue::DoubledSlash], Some("src/lib".to_string())))
+        );
+        assert_eq!(
+            check("src\\windows\\path"),
+            Some((
+                vec![PathIssue::Backslash],
+                Some("src/windows/path".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn strips_the_repository_root_from_absolute_paths() {
+        assert_eq!(
+            check("/home/ci/repo/src/"),
+            Some((vec![PathIssue::AbsolutePath], Some("/src/".to_string())))
+        );
+        assert_eq!(
+            check("/home/someone/else/src"),
+            Some((vec![PathIssue::AbsolutePath], None))
+        );
+        assert_eq!(
+            check("C:/repo/src"),
+            Some((vec![PathIssue::AbsolutePath], None))
+        );
+    }
+
+    #[test]
+    fn trusts_directories_that_exist_in_the_repository() {
+        let files = FileIndex::from_entries([RepoEntry {
+            path: PathBuf::from("builds/ci/run.sh"),
+            kind: EntryKind::File,
+        }]);
+        assert_eq!(check_pattern("/builds/ci/", Some(&files), None), None);
+        assert_eq!(
+            check_pattern("/tmp/fixtures/", Some(&files), None),
+            Some((vec![PathIssue::AbsolutePath], None))
+        );
+        // Without the file list there is nothing to judge by
+        assert_eq!(check_pattern("/tmp/fixtures/", None, None), None);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::error::Result;
+use crate::file_index::FileIndex;
+use crate::files::EntryKind;
+use crate::matcher::RuleMatcher;
+use crate::parser::CodeOwnerRule;
+use rustc_hash::FxHashSet;
+use std::path::{Path, PathBuf};
+
+/// A pattern that works but could be written more clearly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleLint {
+    /// `**/*.md` where `*.md` means the same
+    RedundantPrefix,
+    /// `/src/**` where `/src/` means the same
+    RedundantSuffix,
+    /// `docs/*`, which skips nested files, where `docs/` was likely meant
+    SingleStarDirectory,
+    /// A wildcard that matches exactly one existing file
+    NeedlessWildcard,
+}
+
+impl StyleLint {
+    pub const ALL: [StyleLint; 4] = [
+        StyleLint::RedundantPrefix,
+        StyleLint::RedundantSuffix,
+        StyleLint::SingleStarDirectory,
+        StyleLint::NeedlessWildcard,
+    ];
+
+    /// The name used to select the lint, e.g. `redundant_prefix`.
+    pub fn name(self) -> &'static str {
+        match self {
+            StyleLint::RedundantPrefix => "redundant_prefix",
+            StyleLint::RedundantSuffix => "redundant_suffix",
+            StyleLint::SingleStarDirectory => "single_star_directory",
+            StyleLint::NeedlessWildcard => "needless_wildcard",
+        }
+    }
+
+    /// The diagnostic code, e.g. `pattern_style.redundant_prefix`.
+    pub fn code(self) -> &'static str {
+        match self {
+            StyleLint::RedundantPrefix => "pattern_style.redundant_prefix",
+            StyleLint::RedundantSuffix => "pattern_style.redundant_suffix",
+            StyleLint::SingleStarDirectory => "pattern_style.single_star_directory",
+            StyleLint::NeedlessWildcard => "pattern_style.needless_wildcard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        StyleLint::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+}
+
+/// Which style lints to run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StyleLints([bool; 4]);
+
+impl StyleLints {
+    pub fn all() -> Self {
+        StyleLints([true; 4])
+    }
+
+    pub fn enable(&mut self, lint: StyleLint) {
+        self.0[lint as usize] = true;
+    }
+
+    pub fn is_enabled(&self, lint: StyleLint) -> bool {
+        self.0[lint as usize]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.0.contains(&true)
+    }
+
+    /// Whether any enabled lint looks at the repository's files.
+    pub fn needs_files(&self) -> bool {
+        self.is_enabled(StyleLint::SingleStarDirectory)
+            || self.is_enabled(StyleLint::NeedlessWildcard)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleFinding {
+    pub rule: CodeOwnerRule,
+    pub lint: StyleLint,
+    /// The suggested pattern
+    pub replacement: String,
+    /// For `NeedlessWildcard`, the only file the pattern matches
+    pub matched: Option<PathBuf>,
+}
+
+/// Runs the enabled lints. `files` is only needed by the lints that look at
+/// the repository, `single_star_directory` and `needless_wildcard`; see
+/// `StyleLints::needs_files`.
+pub fn validate_pattern_style(
+    rules: &[CodeOwnerRule],
+    lints: &StyleLints,
+    files: Option<&FileIndex>,
+) -> Result<Vec<StyleFinding>> {
+    let mut findings = Vec::new();
+
+    let has_single_star = rules.iter().any(|rule| rule.original_path.ends_with("/*"));
+    let nested = match files {
+        Some(files) if lints.is_enabled(StyleLint::SingleStarDirectory) && has_single_star => {
+            directories_with_subdirectories(files)
+        }
+        _ => FxHashSet::default(),
+    };
+
+    for rule in rules {
+        let original = rule.original_path.as_str();
+        let rewrites = [
+            (StyleLint::RedundantPrefix, redundant_prefix(original)),
+            (StyleLint::RedundantSuffix, redundant_suffix(original)),
+            (
+                StyleLint::SingleStarDirectory,
+                single_star_directory(original, &nested),
+            ),
+        ];
+        for (lint, replacement) in rewrites {
+            if let Some(replacement) = replacement.filter(|_| lints.is_enabled(lint)) {
+                findings.push(StyleFinding {
+                    rule: rule.clone(),
+                    lint,
+                    replacement,
+                    matched: None,
+                });
+            }
+        }
+    }
+
+    if let Some(files) = files.filter(|_| lints.is_enabled(StyleLint::NeedlessWildcard)) {
+        findings.extend(needless_wildcards(rules, files)?);
+        findings.sort_by_key(|finding| finding.rule.line_number);
+    }
+
+    Ok(findings)
+}
+
+fn has_wildcard(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// `**/name` is the same as `name` when `name` has no slash, since such a
+/// pattern already matches at any depth.
+fn redundant_prefix(original: &str) -> Option<String> {
+    let rest = original.strip_prefix("**/")?;
+    let name = rest.strip_suffix('/').unwrap_or(rest);
+    (!name.is_empty() && !name.contains('/') && name != "**").then(|| rest.to_string())
+}
+
+/// `dir/**` is the same as the directory pattern `dir/`, as long as the
+/// anchoring is kept: `src/**` is relative to the root, `src/` is not.
+fn redundant_suffix(original: &str) -> Option<String> {
+    let dir = original.strip_suffix("/**")?;
+    if let Some(name) = dir.strip_prefix("**/") {
+        return (!name.is_empty() && !has_wildcard(name) && !name.contains('/'))
+            .then(|| format!("{}/", name));
+    }
+    let dir = dir.trim_start_matches('/');
+    (!dir.is_empty() && !dir.contains("**")).then(|| format!("/{}/", dir))
+}
+
+/// `dir/*` only covers files directly inside `dir`. Flagged when the
+/// directory has subdirectories whose files are left out.
+fn single_star_directory(original: &str, nested: &FxHashSet<PathBuf>) -> Option<String> {
+    let dir = original.strip_suffix("/*")?.trim_start_matches('/');
+    if dir.is_empty() || has_wildcard(dir) {
+        return None;
+    }
+    nested
+        .contains(Path::new(dir))
+        .then(|| format!("/{}/", dir))
+}
+
+/// The directories that contain at least one other directory.
+fn directories_with_subdirectories(files: &FileIndex) -> FxHashSet<PathBuf> {
+    files
+        .entries()
+        .filter(|entry| entry.kind == EntryKind::Directory)
+        .filter_map(|entry| entry.path.parent().map(Path::to_path_buf))
+        .collect()
+}
+
+/// Wildcard rules that match exactly one file in the repository.
+fn needless_wildcards(rules: &[CodeOwnerRule], files: &FileIndex) -> Result<Vec<StyleFinding>> {
+    let candidates: Vec<&CodeOwnerRule> = rules
+        .iter()
+        .filter(|rule| has_wildcard(&rule.pattern) && !matches!(rule.pattern.as_str(), "*" | "**"))
+        .collect();
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let matcher = RuleMatcher::new(candidates.iter().copied())?;
+    let mut matches: Vec<(usize, Option<PathBuf>)> = vec![(0, None); candidates.len()];
+    let files = files
+        .entries()
+        .filter(|entry| entry.kind == EntryKind::File)
+        .map(|entry| entry.path);
+    for file in files {
+        for idx in matcher.matches(&file) {
+            let (count, first) = &mut matches[idx];
+            *count += 1;
+            first.get_or_insert_with(|| file.clone());
+        }
+    }
+
+    let findings = candidates
+        .into_iter()
+        .zip(matches)
+        .filter_map(|(rule, (count, file))| {
+            let file = file.filter(|_| count == 1)?;
+            let segments = file
+                .iter()
+                .map(|segment| segment.to_str().map(escape_pattern))
+                .collect::<Option<Vec<_>>>()?;
+            Some(StyleFinding {
+                rule: rule.clone(),
+                lint: StyleLint::NeedlessWildcard,
+                replacement: format!("/{}", segments.join("/")),
+                matched: Some(file),
+            })
+        })
+        .collect();
+    Ok(findings)
+}
+
+/// Escapes a path segment so it can be written as a literal pattern.
+fn escape_pattern(segment: &str) -> String {
+    let mut escaped = String::with_capacity(segment.len());
+    for ch in segment.chars() {
+        if ch.is_whitespace() || matches!(ch, '#' | '*' | '?' | '[' | ']' | '{' | '}' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{FileOptions, FileSource};
+    use crate::parser::parse_str;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn lint(source: &str, repo_root: &Path, lint: StyleLint) -> Vec<String> {
+        let (rules, _) = parse_str(source);
+        let mut lints = StyleLints::default();
+        lints.enable(lint);
+        let files =
+            FileIndex::build(repo_root, FileSource::Worktree, FileOptions::DEFAULT).unwrap();
+        validate_pattern_style(&rules, &lints, Some(&files))
+            .unwrap()
+            .into_iter()
+            .map(|finding| finding.replacement)
+            .collect()
+    }
+
+    #[test]
+    fn rewrites_redundant_prefixes_and_suffixes() {
+        let tmp = tempdir().unwrap();
+        let source =
+            "**/*.md @a\n**/docs/api @a\n**/build/ @a\n/src/** @a\nsrc/** @a\n**/lib/** @a\n";
+        assert_eq!(
+            lint(source, tmp.path(), StyleLint::RedundantPrefix),
+            vec!["*.md", "build/"]
+        );
+        assert_eq!(
+            lint(source, tmp.path(), StyleLint::RedundantSuffix),
+            vec!["/src/", "/src/", "lib/"]
+        );
+    }
+
+    #[test]
+    fn flags_single_star_directories_with_nested_files() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("docs/guides")).unwrap();
+        fs::create_dir_all(tmp.path().join("flat")).unwrap();
+
+        let source = "docs/* @a\nflat/* @a\nmissing/* @a\n";
+        assert_eq!(
+            lint(source, tmp.path(), StyleLint::SingleStarDirectory),
+            vec!["/docs/"]
+        );
+    }
+
+    #[test]
+    fn flags_wildcards_matching_one_file() {
+        let tmp = tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("ci")).unwrap();
+        fs::write(tmp.path().join("ci/build job.yml"), "").unwrap();
+        fs::write(tmp.path().join("a.md"), "").unwrap();
+        fs::write(tmp.path().join("b.md"), "").unwrap();
+
+        let source = "* @a\n*.yml @a\n*.md @a\n*.txt @a\n";
+        assert_eq!(
+            lint(source, tmp.path(), StyleLint::NeedlessWildcard),
+            vec!["/ci/build\\ job.yml"]
+        );
+    }
+
+    #[test]
+    fn selects_lints_by_name() {
+        assert_eq!(
+            StyleLint::from_name("needless_wildcard"),
+            Some(StyleLint::NeedlessWildcard)
+        );
+        assert_eq!(StyleLint::from_name("pattern_style"), None);
+        assert!(StyleLints::default().is_empty());
+        assert!(!StyleLints::all().is_empty());
+    }
+}
@@ -1,96 +1,287 @@
+use crate::diagnostic::Diagnostic;
+use crate::error::{Error, Result};
+use crate::files::FileSource;
 use crate::parser::CodeOwnerRule;
+use crate::reporter::Reporter;
+use crate::validators::context::ValidationContext;
+use crate::validators::coverage::validate_coverage;
 use crate::validators::duplicate_patterns::validate_duplicates;
-use crate::validators::exists::validate_directory;
-use std::path::Path;
+use crate::validators::exists::{check_entries, validate_entries_with, EntryIssue};
+use crate::validators::gitignored::validate_gitignored;
+use crate::validators::limits::{validate_limits, Limits};
+use crate::validators::no_owners::validate_no_owners;
+use crate::validators::overbroad::{validate_overbroad, Breadth};
+use crate::validators::path_normalization::{self, validate_path_normalization};
+use crate::validators::pattern_style::{validate_pattern_style, StyleLint, StyleLints};
 use std::time;
 
+const CHECKS: &str = "exists, duplicate_patterns, no_owners, coverage, limits, path_normalization, pattern_style, gitignored, overbroad";
+
 #[derive(Debug, Clone, Default)]
 pub struct ValidatorArgs {
     pub exists: bool,
     pub duplicate_patterns: bool,
+    pub no_owners: bool,
+    pub coverage: bool,
+    pub limits: bool,
+    pub path_normalization: bool,
+    /// Style lints to run; `pattern_style` selects all of them
+    pub pattern_style: StyleLints,
+    pub gitignored: bool,
+    pub overbroad: bool,
+    /// Patterns that may intentionally have no owners
+    pub allow_unowned: Vec<String>,
+    /// Limits that replace the forge's documented ones
+    pub limit_overrides: Limits,
+    /// When the overbroad check flags a rule
+    pub breadth: Breadth,
 }
 
 impl ValidatorArgs {
-    pub fn from_env(args_str: &str) -> Self {
+    /// Parses a list of check names separated by commas or whitespace, so
+    /// multi-line YAML inputs work as well.
+    pub fn from_env(args_str: &str) -> Result<Self> {
         let mut args = ValidatorArgs::default();
 
-        for arg in args_str.split(',') {
-            match arg.trim() {
+        let names = args_str
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|name| !name.is_empty());
+
+        for name in names {
+            match name {
                 "exists" => args.exists = true,
                 "duplicate_patterns" => args.duplicate_patterns = true,
+                "no_owners" => args.no_owners = true,
+                "coverage" => args.coverage = true,
+                "limits" => args.limits = true,
+                "path_normalization" => args.path_normalization = true,
+                "pattern_style" => args.pattern_style = StyleLints::all(),
+                "gitignored" => args.gitignored = true,
+                "overbroad" => args.overbroad = true,
                 "all" => {
                     args.exists = true;
                     args.duplicate_patterns = true;
+                    args.no_owners = true;
+                    args.coverage = true;
+                    args.limits = true;
+                    args.path_normalization = true;
+                    args.pattern_style = StyleLints::all();
+                    args.gitignored = true;
+                    args.overbroad = true;
+                }
+                name if name.starts_with("pattern_style.") => {
+                    let lint = &name["pattern_style.".len()..];
+                    match StyleLint::from_name(lint) {
+                        Some(lint) => args.pattern_style.enable(lint),
+                        None => {
+                            let known: Vec<&str> =
+                                StyleLint::ALL.iter().map(|lint| lint.name()).collect();
+                            return Err(Error::Config(format!(
+                                "unknown pattern_style lint `{}` (expected one of: {})",
+                                lint,
+                                known.join(", ")
+                            )));
+                        }
+                    }
+                }
+                unknown => {
+                    return Err(Error::Config(format!(
+                        "unknown check `{}` (expected one of: {}, all)",
+                        unknown, CHECKS
+                    )))
                 }
-                _ => (),
             }
         }
 
-        args
+        Ok(args)
     }
 
     pub fn should_run_all(&self) -> bool {
-        !self.exists && !self.duplicate_patterns
+        !self.exists
+            && !self.duplicate_patterns
+            && !self.no_owners
+            && !self.coverage
+            && !self.limits
+            && !self.path_normalization
+            && self.pattern_style.is_empty()
+            && !self.gitignored
+            && !self.overbroad
+    }
+
+    fn enabled(&self, name: &str) -> bool {
+        self.should_run_all()
+            || match name {
+                "exists" => self.exists,
+                "duplicate_patterns" => self.duplicate_patterns,
+                "no_owners" => self.no_owners,
+                "coverage" => self.coverage,
+                "limits" => self.limits,
+                "path_normalization" => self.path_normalization,
+                "pattern_style" => !self.pattern_style.is_empty(),
+                "gitignored" => self.gitignored,
+                "overbroad" => self.overbroad,
+                _ => false,
+            }
     }
 }
 
-type ValidatorFn = fn(&[CodeOwnerRule]) -> Vec<CodeOwnerRule>;
+type ValidatorFn = fn(&ValidatorArgs, &ValidationContext, &dyn Reporter) -> Result<Vec<Diagnostic>>;
 
 pub fn run_validator(
     args: &ValidatorArgs,
-    rules: &[CodeOwnerRule],
-) -> Vec<(String, CodeOwnerRule)> {
-    let mut failed_rules = Vec::new();
+    ctx: &ValidationContext,
+    reporter: &dyn Reporter,
+) -> Result<Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
 
     let validators: Vec<(&str, ValidatorFn)> = vec![
-        ("exists", |rules| {
-            let repo_dir = Path::new(".");
-            match validate_directory(repo_dir, rules) {
-                Ok(result) => result,
-                Err(err) => {
-                    eprintln!("❌ Error during 'exists' validation: {}", err);
-                    Vec::new()
+        ("exists", |args, ctx, reporter| {
+            let findings = match exists_walks_alone(args, ctx) {
+                true => {
+                    validate_entries_with(ctx.repo_root, ctx.rules, ctx.file_options, reporter)?
                 }
+                false => check_entries(ctx.files()?, ctx.rules)?,
+            };
+            Ok(findings
+                .iter()
+                .map(|finding| match &finding.issue {
+                    EntryIssue::Missing => Diagnostic::exists(&finding.rule),
+                    EntryIssue::NotADirectory => Diagnostic::not_a_directory(&finding.rule),
+                    EntryIssue::ImplicitDirectory => Diagnostic::implicit_directory(&finding.rule),
+                    EntryIssue::InsideSubmodule { submodule } => {
+                        Diagnostic::inside_submodule(&finding.rule, submodule)
+                    }
+                })
+                .collect())
+        }),
+        ("duplicate_patterns", |_, ctx, _| {
+            Ok(validate_duplicates(ctx.rules)
+                .iter()
+                .map(|rule| Diagnostic::duplicate_pattern(rule, first_definition(rule, ctx.rules)))
+                .collect())
+        }),
+        ("no_owners", |args, ctx, _| {
+            Ok(validate_no_owners(ctx.rules, &args.allow_unowned)
+                .iter()
+                .map(Diagnostic::no_owners)
+                .collect())
+        }),
+        ("coverage", |_, ctx, _| {
+            let coverage = validate_coverage(ctx.rules, ctx.files()?)?;
+            if coverage.unowned.is_empty() {
+                return Ok(Vec::new());
             }
+            Ok(vec![Diagnostic::coverage(&coverage)])
+        }),
+        ("limits", |args, ctx, _| {
+            let limits = Limits::for_forge(ctx.forge).with_overrides(&args.limit_overrides);
+            Ok(validate_limits(ctx.source, ctx.rules, &limits)
+                .iter()
+                .map(|finding| Diagnostic::limit(finding, ctx.forge))
+                .collect())
+        }),
+        ("path_normalization", |_, ctx, _| {
+            let files = match path_normalization::needs_files(ctx.rules) {
+                true => Some(ctx.files()?),
+                false => None,
+            };
+            Ok(validate_path_normalization(ctx.repo_root, ctx.rules, files)
+                .iter()
+                .map(Diagnostic::path_normalization)
+                .collect())
+        }),
+        ("pattern_style", |args, ctx, _| {
+            let lints = style_lints(args);
+            let files = match lints.needs_files() {
+                true => Some(ctx.files()?),
+                false => None,
+            };
+            Ok(validate_pattern_style(ctx.rules, &lints, files)?
+                .iter()
+                .map(Diagnostic::pattern_style)
+                .collect())
+        }),
+        ("gitignored", |_, ctx, _| {
+            Ok(validate_gitignored(ctx.rules, ctx.files()?)?
+                .iter()
+                .map(Diagnostic::gitignored)
+                .collect())
+        }),
+        ("overbroad", |args, ctx, _| {
+            Ok(validate_overbroad(ctx.rules, ctx.files()?, &args.breadth)?
+                .iter()
+                .map(Diagnostic::overbroad)
+                .collect())
         }),
-        ("duplicate_patterns", validate_duplicates),
     ];
 
     for (name, validator_fn) in validators {
-        if args.should_run_all()
-            || (name == "exists" && args.exists)
-            || (name == "duplicate_patterns" && args.duplicate_patterns)
-        {
+        if args.enabled(name) {
+            reporter.validator_started(name);
             let now = time::Instant::now();
-            let results = validator_fn(rules);
-            let num_failures = results.len();
+            let results = validator_fn(args, ctx, reporter)?;
+            let elapsed = now.elapsed();
 
-            for rule in results {
-                failed_rules.push((name.to_string(), rule));
+            for diagnostic in &results {
+                reporter.diagnostic(diagnostic);
             }
 
-            println!(
-                "✓ {} validation completed in {:?} ({} issues found)",
-                name,
-                now.elapsed(),
-                num_failures
-            );
+            reporter.validator_finished(name, elapsed, results.len());
+            diagnostics.extend(results);
         }
     }
 
-    failed_rules
+    Ok(diagnostics)
+}
+
+/// The style lints to run.
+fn style_lints(args: &ValidatorArgs) -> StyleLints {
+    if args.should_run_all() {
+        StyleLints::all()
+    } else {
+        args.pattern_style
+    }
+}
+
+/// Whether `exists` can walk the working tree on its own, skipping
+/// directories no rule reaches and stopping once every rule matched,
+/// instead of listing every file. That only pays off when no other check
+/// needs the list, and `.gitignore` filtering needs the whole tree.
+fn exists_walks_alone(args: &ValidatorArgs, ctx: &ValidationContext) -> bool {
+    let others_need_files = args.enabled("coverage")
+        || args.enabled("gitignored")
+        || args.enabled("overbroad")
+        || (args.enabled("path_normalization") && path_normalization::needs_files(ctx.rules))
+        || (args.enabled("pattern_style") && style_lints(args).needs_files());
+    !others_need_files
+        && !ctx.has_files()
+        && ctx.file_source == FileSource::Worktree
+        && !ctx.file_options.exclude_ignored
+}
+
+/// The earlier rule a duplicate collides with.
+fn first_definition<'a>(
+    rule: &CodeOwnerRule,
+    rules: &'a [CodeOwnerRule],
+) -> Option<&'a CodeOwnerRule> {
+    rules.iter().find(|other| {
+        other.line_number < rule.line_number
+            && (other.pattern == rule.pattern || other.original_path == rule.original_path)
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::parser::CodeOwnerRule;
+    use crate::reporter::NoopReporter;
 
     fn rule(pattern: &str, original: &str) -> CodeOwnerRule {
         CodeOwnerRule {
             pattern: pattern.trim_matches('/').to_string(),
             original_path: original.to_string(),
             owners: vec!["@x".to_string()],
+            line_number: 0,
         }
     }
 
@@ -102,7 +293,8 @@ mod tests {
             rule("dup.txt", "dup.txt"),
         ];
         let args = ValidatorArgs::default();
-        let failures = run_validator(&args, &rules);
+        let failures =
+            run_validator(&args, &ValidationContext::new(&rules), &NoopReporter).unwrap();
         assert!(!failures.is_empty());
     }
 
@@ -111,46 +303,209 @@ mod tests {
         let rules = vec![rule("notfound.txt", "notfound.txt")];
         let args = ValidatorArgs {
             exists: true,
-            duplicate_patterns: false,
+            ..Default::default()
         };
-        let failures = run_validator(&args, &rules);
+        let failures =
+            run_validator(&args, &ValidationContext::new(&rules), &NoopReporter).unwrap();
         assert_eq!(failures.len(), 1);
-        assert_eq!(failures[0].0, "exists");
+        assert_eq!(failures[0].code, "exists");
     }
 
     #[test]
     fn runs_only_duplicates_when_enabled() {
         let rules = vec![rule("x.txt", "x.txt"), rule("x.txt", "x.txt")];
         let args = ValidatorArgs {
-            exists: false,
             duplicate_patterns: true,
+            ..Default::default()
         };
-        let failures = run_validator(&args, &rules);
+        let failures =
+            run_validator(&args, &ValidationContext::new(&rules), &NoopReporter).unwrap();
         assert_eq!(failures.len(), 1);
-        assert_eq!(failures[0].0, "duplicate_patterns");
+        assert_eq!(failures[0].code, "duplicate_patterns");
+    }
+
+    #[test]
+    fn propagates_validator_errors() {
+        // The parser rejects this glob, but library callers can build rules directly
+        let rules = vec![rule("src/[", "/src/[")];
+        let args = ValidatorArgs {
+            exists: true,
+            ..Default::default()
+        };
+        let result = run_validator(&args, &ValidationContext::new(&rules), &NoopReporter);
+        assert!(matches!(
+            result,
+            Err(crate::error::Error::InvalidGlob { pattern, .. }) if pattern == "src/["
+        ));
+    }
+
+    #[test]
+    fn reports_events_to_reporter() {
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl Reporter for Recorder {
+            fn validator_started(&self, name: &str) {
+                self.0.lock().unwrap().push(format!("start {}", name));
+            }
+            fn validator_finished(&self, name: &str, _elapsed: Duration, issues: usize) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("finish {} {}", name, issues));
+            }
+            fn diagnostic(&self, diagnostic: &Diagnostic) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("diagnostic {}", diagnostic.code));
+            }
+        }
+
+        let rules = vec![rule("x.txt", "x.txt"), rule("x.txt", "x.txt")];
+        let args = ValidatorArgs {
+            duplicate_patterns: true,
+            ..Default::default()
+        };
+        let recorder = Recorder::default();
+        run_validator(&args, &ValidationContext::new(&rules), &recorder).unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                "start duplicate_patterns",
+                "diagnostic duplicate_patterns",
+                "finish duplicate_patterns 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_no_owners_with_allow_list() {
+        let mut unowned = rule("vendor", "/vendor/");
+        unowned.owners.clear();
+        let mut allowed = rule("generated", "/generated/");
+        allowed.owners.clear();
+
+        let args = ValidatorArgs {
+            no_owners: true,
+            allow_unowned: vec!["/generated/".to_string()],
+            ..Default::default()
+        };
+        let failures = run_validator(
+            &args,
+            &ValidationContext::new(&[unowned, allowed]),
+            &NoopReporter,
+        )
+        .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].code, "no_owners");
+        assert_eq!(failures[0].severity, crate::diagnostic::Severity::Warning);
+    }
+
+    #[test]
+    fn runs_limits_against_the_source() {
+        let source = b"/src/ @a @b @c\n";
+        let (rules, _) = crate::parser::parse_bytes(source);
+        let args = ValidatorArgs {
+            limits: true,
+            limit_overrides: Limits {
+                max_owners_per_rule: Some(2),
+                ..Limits::default()
+            },
+            ..Default::default()
+        };
+        let ctx = ValidationContext::new(&rules).with_source(source);
+        let failures = run_validator(&args, &ctx, &NoopReporter).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].code, "limits");
+    }
+
+    #[test]
+    fn exists_walks_alone_with_the_same_findings() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("src/nested")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("docs")).unwrap();
+        std::fs::write(tmp.path().join("src/nested/lib.rs"), "").unwrap();
+        std::fs::write(tmp.path().join("docs/guide.md"), "").unwrap();
+        let (rules, _) = crate::parser::parse_str(
+            "/src/ @a\n/docs @a\n/docs/guide.md/ @a\n*.rs @a\n/src/**/*.py @a\n/missing @a\n",
+        );
+
+        let findings = |args: &ValidatorArgs| -> Vec<(String, String)> {
+            let ctx = ValidationContext::new(&rules)
+                .with_repo_root(tmp.path())
+                .with_file_source(FileSource::Worktree);
+            assert_eq!(exists_walks_alone(args, &ctx), !args.coverage);
+            run_validator(args, &ctx, &NoopReporter)
+                .unwrap()
+                .into_iter()
+                .filter(|diagnostic| diagnostic.code != "coverage")
+                .map(|diagnostic| (diagnostic.code.to_string(), diagnostic.message))
+                .collect()
+        };
+        let alone = findings(&ValidatorArgs {
+            exists: true,
+            ..Default::default()
+        });
+        let shared = findings(&ValidatorArgs {
+            exists: true,
+            coverage: true,
+            ..Default::default()
+        });
+        assert_eq!(alone.len(), 4);
+        assert_eq!(alone, shared);
     }
 
     #[test]
     fn from_env_splits_checks() {
-        let args = ValidatorArgs::from_env("exists,duplicate_patterns");
+        let args = ValidatorArgs::from_env("exists,duplicate_patterns").unwrap();
         assert!(args.exists);
         assert!(args.duplicate_patterns);
     }
 
     #[test]
     fn from_env_handles_all() {
-        let args = ValidatorArgs::from_env("all");
+        let args = ValidatorArgs::from_env("all").unwrap();
         assert!(args.exists);
         assert!(args.duplicate_patterns);
+        assert!(args.no_owners);
+        assert!(args.coverage);
     }
 
     #[test]
     fn from_env_handles_whitespace() {
-        let args = ValidatorArgs::from_env(" exists , duplicate_patterns ");
+        let args = ValidatorArgs::from_env(" exists , duplicate_patterns ").unwrap();
+        assert!(args.exists);
+        assert!(args.duplicate_patterns);
+    }
+
+    #[test]
+    fn from_env_handles_newlines() {
+        let args = ValidatorArgs::from_env("exists\nduplicate_patterns\n").unwrap();
         assert!(args.exists);
         assert!(args.duplicate_patterns);
     }
 
+    #[test]
+    fn from_env_selects_single_style_lints() {
+        let args = ValidatorArgs::from_env("pattern_style.redundant_suffix").unwrap();
+        assert!(args.pattern_style.is_enabled(StyleLint::RedundantSuffix));
+        assert!(!args.pattern_style.is_enabled(StyleLint::RedundantPrefix));
+        assert!(!args.should_run_all());
+
+        let result = ValidatorArgs::from_env("pattern_style.bogus");
+        assert!(matches!(result, Err(Error::Config(message)) if message.contains("`bogus`")));
+    }
+
+    #[test]
+    fn from_env_rejects_unknown_checks() {
+        let result = ValidatorArgs::from_env("exists,exist");
+        assert!(matches!(result, Err(Error::Config(message)) if message.contains("`exist`")));
+    }
+
     #[test]
     fn should_run_all_when_none_specified() {
         let args = ValidatorArgs::default();
@@ -161,7 +516,7 @@ mod tests {
     fn not_should_run_all_when_any_specified() {
         let args = ValidatorArgs {
             exists: true,
-            duplicate_patterns: false,
+            ..Default::default()
         };
         assert!(!args.should_run_all());
     }